@@ -37,22 +37,37 @@ indicatif = "0.17"
 async-stream = "0.3"
 colored = "2.1"
 tokio-stream = "0.1"
+regex = "1.10"
+globset = "0.4"
+fuzzy-matcher = "0.3"
+humansize = "2.1"
+termimad = "0.34"
+toml = "0.8"
+sha1 = "0.11"
+flate2 = "1.1"
+ratatui = "0.30"
+syntect = { version = "5.2", default-features = false, features = ["default-syntaxes", "default-themes", "regex-fancy"] }
 
 */
 use clap::{Parser, Subcommand};
 use std::env;
+use std::io::IsTerminal;
 use tokio::io::{AsyncWriteExt, BufWriter};
 use tokio::time::sleep;
 use std::sync::Arc;
 use anyhow::{anyhow, Context, Result};
 use colored::*;
 use tokio_stream::StreamExt;
+use futures::TryStreamExt;
 use std::collections::VecDeque;
 use indicatif::{ProgressBar, ProgressStyle};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use humansize::{format_size, BINARY};
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
 use std::fs;
 use std::{
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     path::{Path, PathBuf},
     time::Duration,
 };
@@ -60,22 +75,182 @@ const GITHUB_API_URL: &str = "https://api.github.com";
 const USER_AGENT: &str = "rhythmcache.t.me/gh-tree/0.2.0";
 const MAX_RETRIES: u32 = 16;
 const INITIAL_DELAY: Duration = Duration::from_secs(3);
-#[derive(Debug, Deserialize)]
+/// The browser waits on each pull, so it gives up on a failing download quickly.
+const BROWSE_MAX_RETRIES: u32 = 1;
+const MAX_CONCURRENT_REQUESTS: usize = 8;
+const GRAPHQL_BATCH_SIZE: usize = 50;
+const COMPARE_FILE_LIMIT: usize = 300;
+const BATCH_MIN_RATE_LIMIT: u64 = 50;
+const MAX_SEARCH_RETRIES: u32 = 3;
+const MAX_SEARCH_WAIT: Duration = Duration::from_secs(60);
+const PLACEHOLDER_INDEX: &str = ".ghtree-index.json";
+
+/// Where a command prints. Batch jobs buffer their output and warnings, so each job's
+/// are printed together under its header instead of interleaving with other jobs.
+#[derive(Default)]
+struct Output {
+    buffer: Option<std::sync::Mutex<JobOutput>>,
+    /// The latest spinner, which warnings are printed above
+    progress: std::sync::Mutex<Option<ProgressBar>>,
+}
+
+#[derive(Default)]
+struct JobOutput {
+    stdout: String,
+    stderr: String,
+}
+
+impl Output {
+    fn buffered() -> Self {
+        Self {
+            buffer: Some(Default::default()),
+            ..Self::default()
+        }
+    }
+
+    fn is_buffered(&self) -> bool {
+        self.buffer.is_some()
+    }
+
+    /// Lets `write!` and `writeln!` print to the output.
+    fn write_fmt(&self, args: std::fmt::Arguments) {
+        match &self.buffer {
+            Some(buffer) => {
+                use std::fmt::Write;
+                let _ = buffer.lock().unwrap().stdout.write_fmt(args);
+            }
+            None => print!("{}", args),
+        }
+    }
+
+    /// Prints a warning or notice to stderr without garbling the spinner.
+    fn warn(&self, message: impl std::fmt::Display) {
+        match &self.buffer {
+            Some(buffer) => {
+                use std::fmt::Write;
+                let _ = writeln!(buffer.lock().unwrap().stderr, "{}", message);
+            }
+            None => match &*self.progress.lock().unwrap() {
+                Some(progress) => progress.suspend(|| eprintln!("{}", message)),
+                None => eprintln!("{}", message),
+            },
+        }
+    }
+
+    /// Starts a spinner, hidden for batch jobs as concurrent spinners would draw over each other.
+    fn progress_bar(&self, message: &str) -> ProgressBar {
+        if self.is_buffered() {
+            return ProgressBar::hidden();
+        }
+
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {msg}")
+                .unwrap()
+                .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈"),
+        );
+        pb.set_message(message.to_string());
+        pb.enable_steady_tick(Duration::from_millis(100));
+        *self.progress.lock().unwrap() = Some(pb.clone());
+        pb
+    }
+
+    fn into_job_output(self) -> JobOutput {
+        self.buffer.map(|buffer| buffer.into_inner().unwrap()).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
 struct RepoInfo {
     default_branch: String,
+    #[serde(default)]
+    name: String,
+    full_name: Option<String>,
+    description: Option<String>,
+    html_url: Option<String>,
+    homepage: Option<String>,
+    #[serde(default)]
+    stargazers_count: u64,
+    #[serde(default)]
+    forks_count: u64,
+    #[serde(default)]
+    open_issues_count: u64,
+    #[serde(default)]
+    subscribers_count: u64,
+    license: Option<RepoLicense>,
+    #[serde(default)]
+    topics: Vec<String>,
+    /// Size in kilobytes
+    #[serde(default)]
+    size: u64,
+    language: Option<String>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    fork: bool,
+    #[serde(default)]
+    private: bool,
+    pushed_at: Option<String>,
+    created_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct RepoLicense {
+    name: String,
+    spdx_id: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct TreeResponse {
+    sha: String,
     tree: Vec<TreeItem>,
     truncated: bool,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Deserialize, Serialize, Clone)]
 struct TreeItem {
     path: String,
+    mode: String,
     #[serde(rename = "type")]
     item_type: String,
+    sha: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u64>,
+}
+
+/// A saved copy of a repository tree that `view --from` can render offline.
+#[derive(Debug, Deserialize, Serialize)]
+struct TreeSnapshot {
+    repo: String,
+    #[serde(rename = "ref")]
+    git_ref: String,
+    commit: String,
+    fetched_at: String,
+    tree: Vec<TreeItem>,
+}
+
+fn write_tree_snapshot(path: &Path, snapshot: &TreeSnapshot) -> Result<()> {
+    let json = serde_json::to_string_pretty(snapshot)?;
+    fs::write(path, json).with_context(|| format!("Failed to write snapshot: {}", path.display()))
+}
+
+fn read_tree_snapshot(path: &Path) -> Result<TreeSnapshot> {
+    let json = fs::read_to_string(path).with_context(|| format!("Failed to read snapshot: {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Failed to parse snapshot: {}", path.display()))
+}
+
+#[derive(Debug, Deserialize)]
+struct Branch {
+    name: String,
+    commit: BranchCommit,
+    #[serde(default)]
+    protected: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct BranchCommit {
+    sha: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -144,6 +319,11 @@ fn parse_github_url(repo_url: &str) -> Option<(String, String)> {
     None
 }
 
+fn parse_repo_arg(repo: Option<&str>) -> Result<(String, String)> {
+    let repo = repo.ok_or_else(|| anyhow!("A repository is required (--repo or --repos-file)"))?;
+    parse_github_url(repo).ok_or_else(|| anyhow!("Invalid GitHub URL"))
+}
+
 async fn fetch_repo_info(user: &str, repo: &str, config: &Config) -> Result<RepoInfo> {
     let url = format!("{}/repos/{}/{}", GITHUB_API_URL, user, repo);
 
@@ -185,13 +365,109 @@ async fn fetch_repo_info(user: &str, repo: &str, config: &Config) -> Result<Repo
         .context("Failed to parse repository info")
 }
 
+fn next_page_url(response: &reqwest::Response) -> Option<String> {
+    let link = response
+        .headers()
+        .get(reqwest::header::LINK)?
+        .to_str()
+        .ok()?;
+
+    link.split(',').find_map(|part| {
+        let (url, rel) = part.split_once(';')?;
+        rel.contains("rel=\"next\"")
+            .then(|| url.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+    })
+}
+
+/// Fetches every page of a list endpoint by following the `Link` header.
+async fn fetch_all_pages<T: serde::de::DeserializeOwned>(
+    url: &str,
+    config: &Config,
+) -> Result<Vec<T>> {
+    fetch_pages(url, &[], config, None).await
+}
+
+/// Like `fetch_all_pages`, but stops once `limit` items have been collected.
+/// `query` is encoded onto the first request; later pages follow the `Link` header.
+async fn fetch_pages<T: serde::de::DeserializeOwned>(
+    url: &str,
+    query: &[(&str, &str)],
+    config: &Config,
+    limit: Option<usize>,
+) -> Result<Vec<T>> {
+    let per_page = limit.unwrap_or(100).clamp(1, 100).to_string();
+    let mut request = Some(
+        config
+            .client
+            .get(url)
+            .query(query)
+            .query(&[("per_page", per_page.as_str())]),
+    );
+    let mut items = Vec::new();
+
+    while let Some(builder) = request.take() {
+        if limit.is_some_and(|limit| items.len() >= limit) {
+            break;
+        }
+
+        let response = builder
+            .headers(config.get_headers())
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {}", url))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+        }
+
+        request = next_page_url(&response).map(|next| config.client.get(next));
+        let page: Vec<T> = response.json().await.context("Failed to parse response")?;
+        items.extend(page);
+    }
+
+    if let Some(limit) = limit {
+        items.truncate(limit);
+    }
+
+    Ok(items)
+}
+
+/// Uses the given branch or falls back to the repository's default branch.
+async fn resolve_branch(
+    user: &str,
+    repo: &str,
+    branch: Option<String>,
+    config: &Config,
+) -> Result<String> {
+    match branch {
+        Some(branch) => Ok(branch),
+        None => Ok(fetch_repo_info(user, repo, config).await?.default_branch),
+    }
+}
+
 async fn fetch_tree_recursive(
     user: &str,
     repo: &str,
     sha: &str,
     config: &Config,
-    progress: &ProgressBar,
+    out: &Output,
 ) -> Result<Vec<TreeItem>> {
+    Ok(fetch_tree(user, repo, sha, config, out).await?.tree)
+}
+
+async fn fetch_tree(
+    user: &str,
+    repo: &str,
+    sha: &str,
+    config: &Config,
+    out: &Output,
+) -> Result<TreeResponse> {
     let url = format!(
         "{}/repos/{}/{}/git/trees/{}?recursive=1",
         GITHUB_API_URL, user, repo, sha
@@ -218,15 +494,162 @@ async fn fetch_tree_recursive(
     let tree_response: TreeResponse = response.json().await.context("Failed to parse tree data")?;
 
     if tree_response.truncated {
-        progress.println("Warning: Repository tree is truncated due to size limitations");
+        out.warn("Warning: Repository tree is truncated due to size limitations");
+    }
+
+    Ok(tree_response)
+}
+
+async fn create_placeholder_file(path: &Path, size: Option<u64>) -> Result<()> {
+    let file = tokio::fs::File::create(path)
+        .await
+        .with_context(|| format!("Failed to create file: {}", path.display()))?;
+
+    // Extending with set_len leaves a hole, so no blocks are allocated
+    if let Some(size) = size {
+        file.set_len(size)
+            .await
+            .with_context(|| format!("Failed to resize file: {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct ReconcileSummary {
+    added: usize,
+    unchanged: usize,
+    kept: usize,
+    reset: usize,
+    pruned: usize,
+    /// Paths that are placeholders after the update
+    placeholders: HashSet<String>,
+}
+
+/// Brings an existing placeholder tree in line with the remote tree without
+/// clobbering files that have real content, unless `force` is set. `index` is the
+/// tree's placeholder index, if it has one.
+async fn reconcile_placeholder_structure(
+    tree_items: &[TreeItem],
+    base_path: &Path,
+    index: Option<&PlaceholderIndex>,
+    sized: bool,
+    force: bool,
+    prune: bool,
+    progress: &ProgressBar,
+) -> Result<ReconcileSummary> {
+    fs::create_dir_all(base_path).context("Failed to create base directory")?;
+    let mut summary = ReconcileSummary::default();
+
+    for item in tree_items {
+        let path = base_path.join(&item.path);
+
+        match item.item_type.as_str() {
+            "tree" => {
+                fs::create_dir_all(&path)
+                    .with_context(|| format!("Failed to create directory: {}", path.display()))?;
+            }
+            "blob" => {
+                let size = sized.then(|| item.size.unwrap_or(0));
+
+                match fs::symlink_metadata(&path) {
+                    Err(_) => {
+                        if let Some(parent) = path.parent() {
+                            fs::create_dir_all(parent).with_context(|| {
+                                format!("Failed to create parent directory: {}", parent.display())
+                            })?;
+                        }
+                        create_placeholder_file(&path, size).await?;
+                        summary.added += 1;
+                    }
+                    Ok(metadata) if is_placeholder(index, &item.path, &path, &metadata) => {
+                        if size.is_some_and(|size| size != metadata.len()) {
+                            create_placeholder_file(&path, size).await?;
+                        }
+                        summary.unchanged += 1;
+                    }
+                    Ok(metadata) if force => {
+                        if metadata.is_dir() {
+                            fs::remove_dir_all(&path)
+                        } else {
+                            fs::remove_file(&path)
+                        }
+                        .with_context(|| format!("Failed to remove: {}", path.display()))?;
+                        create_placeholder_file(&path, size).await?;
+                        summary.reset += 1;
+                    }
+                    Ok(_) => {
+                        summary.kept += 1;
+                        continue;
+                    }
+                }
+                summary.placeholders.insert(item.path.clone());
+            }
+            _ => continue,
+        }
+
+        progress.set_message(format!("Processing: {}", item.path));
+    }
+
+    if prune {
+        progress.set_message("Pruning removed entries...");
+        let remote_files: HashSet<&str> = tree_items
+            .iter()
+            .filter(|item| item.item_type == "blob")
+            .map(|item| item.path.as_str())
+            .collect();
+
+        for (relative, path) in collect_local_files(base_path)? {
+            if remote_files.contains(relative.as_str()) {
+                continue;
+            }
+
+            let metadata = fs::symlink_metadata(&path)
+                .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+            if force || is_placeholder(index, &relative, &path, &metadata) {
+                fs::remove_file(&path).with_context(|| format!("Failed to remove: {}", path.display()))?;
+                summary.pruned += 1;
+            } else {
+                summary.kept += 1;
+            }
+        }
+
+        let remote_dirs: HashSet<PathBuf> = tree_items
+            .iter()
+            .filter(|item| item.item_type == "tree")
+            .map(|item| base_path.join(&item.path))
+            .collect();
+        for dir in collect_local_dirs(base_path)? {
+            let is_empty = fs::read_dir(&dir)
+                .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+                .next()
+                .is_none();
+            if is_empty && !remote_dirs.contains(&dir) {
+                fs::remove_dir(&dir).with_context(|| format!("Failed to remove: {}", dir.display()))?;
+                summary.pruned += 1;
+            }
+        }
     }
 
-    Ok(tree_response.tree)
+    progress.finish_with_message(format!("Structure updated at: {}", base_path.display()));
+    Ok(summary)
+}
+
+fn print_reconcile_summary(out: &Output, summary: &ReconcileSummary) {
+    writeln!(
+        out,
+        "Added: {}, Unchanged: {}, Kept: {}, Reset: {}, Pruned: {}",
+        summary.added, summary.unchanged, summary.kept, summary.reset, summary.pruned
+    );
+    if summary.kept > 0 {
+        writeln!(out, "Kept files have real content and were left alone, use --force to reset or prune them");
+    }
 }
 
 async fn create_placeholder_structure(
-    tree_items: Vec<TreeItem>,
+    tree_items: &[TreeItem],
     base_path: &Path,
+    sized: bool,
     progress: &ProgressBar,
 ) -> Result<()> {
     let mut created_dirs = HashSet::new();
@@ -246,17 +669,15 @@ async fn create_placeholder_structure(
                 }
             }
             "blob" => {
-                if let Some(parent) = path.parent() {
-                    if !created_dirs.contains(parent) {
-                        fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directory: {}", parent.display())
-                        })?;
-                        created_dirs.insert(parent.to_path_buf());
-                    }
+                if let Some(parent) = path.parent()
+                    && !created_dirs.contains(parent)
+                {
+                    fs::create_dir_all(parent).with_context(|| {
+                        format!("Failed to create parent directory: {}", parent.display())
+                    })?;
+                    created_dirs.insert(parent.to_path_buf());
                 }
-                tokio::fs::File::create(&path)
-    .await
-    .with_context(|| format!("Failed to create file: {}", path.display()))?;
+                create_placeholder_file(&path, sized.then(|| item.size.unwrap_or(0))).await?;
             }
             _ => continue,
         }
@@ -271,184 +692,2774 @@ async fn create_placeholder_structure(
 
 #[derive(Default)]
 struct TreeNode {
+    path: String,
     children: BTreeMap<String, TreeNode>,
     item_type: Option<String>,
+    mode: Option<String>,
+    change: Option<ComparedFile>,
+    last_commit: Option<LastCommit>,
 }
 
-fn print_tree_colored(
+impl TreeNode {
+    fn insert(&mut self, path: &str) -> &mut TreeNode {
+        let mut current = self;
+        let mut end = 0;
+        for part in path.split('/') {
+            end += part.len();
+            let node_path = &path[..end];
+            end += 1;
+
+            current = current
+                .children
+                .entry(part.to_string())
+                .or_insert_with(|| TreeNode {
+                    path: node_path.to_string(),
+                    ..Default::default()
+                });
+        }
+        current
+    }
+
+    fn is_dir(&self) -> bool {
+        self.item_type.as_deref() == Some("tree")
+    }
+
+    /// Returns the number of folders and files below this node.
+    fn counts(&self) -> (usize, usize) {
+        self.children.values().fold((0, 0), |(folders, files), child| {
+            let (sub_folders, sub_files) = child.counts();
+            match child.item_type.as_deref() {
+                Some("tree") => (folders + sub_folders + 1, files + sub_files),
+                Some("blob") => (folders + sub_folders, files + sub_files + 1),
+                _ => (folders + sub_folders, files + sub_files),
+            }
+        })
+    }
+}
+
+fn print_nested(out: &Output, node: &TreeNode, prefix: &str, label: &dyn Fn(&str, &TreeNode) -> String) {
+    let items: Vec<_> = node.children.iter().collect();
+
+    for (i, (name, child)) in items.iter().enumerate() {
+        let is_last_item = i == items.len() - 1;
+        let connector = if is_last_item {
+            "└── "
+        } else {
+            "├── "
+        };
+
+        writeln!(out, "{}{}{}", prefix, connector, label(name, child));
+
+        let new_prefix = format!("{}{}", prefix, if is_last_item { "    " } else { "│   " });
+        print_nested(out, child, &new_prefix, label);
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum TreeFormat {
+    /// Indented tree for the terminal
+    Text,
+    /// Self-contained page with collapsible folders linking to GitHub
+    Html,
+    /// Tree inside a fenced code block
+    Markdown,
+    /// Graphviz graph of the folders
+    Dot,
+    /// Mermaid graph of the folders
+    Mermaid,
+}
+
+/// Normalizes a `--folder` argument, where `None` is the repository root.
+fn folder_prefix(folder: Option<&str>) -> Option<&str> {
+    folder.map(|f| f.trim_matches('/')).filter(|f| !f.is_empty())
+}
+
+/// Whether `path` is the folder itself or below it, matching whole path segments.
+fn in_folder(prefix: Option<&str>, path: &str) -> bool {
+    prefix.is_none_or(|prefix| {
+        path.strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    })
+}
+
+fn build_tree_node(
     tree_items: Vec<TreeItem>,
     progress: &ProgressBar,
-    colored: bool,
-) -> Result<()> {
+    last_commits: &HashMap<String, LastCommit>,
+    changes: &HashMap<String, ComparedFile>,
+) -> TreeNode {
     let mut structure = TreeNode::default();
-    let mut file_count = 0;
-    let mut folder_count = 0;
 
     for item in tree_items {
-        let mut current = &mut structure;
-
-        for part in item.path.split('/') {
-            current = current.children.entry(part.to_string()).or_default();
-        }
-
+        let current = structure.insert(&item.path);
         current.item_type = Some(item.item_type.clone());
-
-        match item.item_type.as_str() {
-            "blob" => file_count += 1,
-            "tree" => folder_count += 1,
-            _ => {}
-        }
+        current.mode = Some(item.mode.clone());
+        current.last_commit = last_commits.get(&item.path).cloned();
+        current.change = changes.get(&item.path).cloned();
 
         progress.inc(1);
         progress.set_message(format!("Processing: {}", item.path));
     }
 
     progress.finish_and_clear();
+    structure
+}
 
-    fn print_nested(node: &TreeNode, prefix: &str, _is_last: bool, colored: bool) {
-        let items: Vec<_> = node.children.iter().collect();
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
 
-        for (i, (name, child)) in items.iter().enumerate() {
-            let is_last_item = i == items.len() - 1;
-            let connector = if is_last_item {
-                "└── "
+fn print_tree_html(out: &Output, structure: &TreeNode, user: &str, repo: &str, git_ref: &str) {
+    fn render(node: &TreeNode, path: &str, user: &str, repo: &str, git_ref: &str, out: &mut String) {
+        out.push_str("<ul>\n");
+        for (name, child) in &node.children {
+            let child_path = if path.is_empty() {
+                name.clone()
             } else {
-                "├── "
+                format!("{}/{}", path, name)
             };
-
-            let (icon, name_colored) = match child.item_type.as_deref() {
-                Some("tree") => (
-                    if colored {
-                        "📁".blue().to_string()
-                    } else {
-                        "".to_string()
-                    },
-                    if colored {
-                        name.blue().to_string()
-                    } else {
-                        name.to_string()
-                    },
-                ),
-                Some("blob") => (
-                    if colored {
-                        "📄".green().to_string()
-                    } else {
-                        "".to_string()
-                    },
-                    if colored {
-                        name.green().to_string()
-                    } else {
-                        name.to_string()
-                    },
-                ),
-                _ => (
-                    if colored {
-                        "❓".yellow().to_string()
-                    } else {
-                        "".to_string()
-                    },
-                    if colored {
-                        name.yellow().to_string()
-                    } else {
-                        name.to_string()
-                    },
-                ),
-            };
-
-            println!("{}{}{} {}", prefix, connector, icon, name_colored);
-
-            let new_prefix = format!("{}{}", prefix, if is_last_item { "    " } else { "│   " });
-            print_nested(child, &new_prefix, is_last_item, colored);
+            let url = github_url(user, repo, git_ref, &child_path, child.is_dir());
+            let link = format!("<a href=\"{}\">{}</a>", html_escape(&url), html_escape(name));
+            let commit = child
+                .last_commit
+                .as_ref()
+                .map(|c| {
+                    format!(
+                        " <span class=\"commit\">{} {}</span>",
+                        html_escape(&c.oid[..c.oid.len().min(7)]),
+                        html_escape(&c.message_headline)
+                    )
+                })
+                .unwrap_or_default();
+
+            if child.is_dir() {
+                out.push_str(&format!("<li><details><summary>📁 {}{}</summary>\n", link, commit));
+                render(child, &child_path, user, repo, git_ref, out);
+                out.push_str("</details></li>\n");
+            } else {
+                out.push_str(&format!("<li>📄 {}{}</li>\n", link, commit));
+            }
         }
+        out.push_str("</ul>\n");
     }
 
-    print_nested(&structure, "", true, colored);
-
-    let summary = format!(
-        "\nTotal folders: {}\nTotal files: {}",
-        if colored {
-            folder_count.to_string().blue().to_string()
-        } else {
-            folder_count.to_string()
-        },
-        if colored {
-            file_count.to_string().green().to_string()
-        } else {
-            file_count.to_string()
-        }
+    let title = format!("{}/{}", user, repo);
+    let mut body = String::new();
+    render(structure, "", user, repo, git_ref, &mut body);
+    let (folders, files) = structure.counts();
+
+    writeln!(
+        out,
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>{title}</title>
+<style>
+body {{ font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; font-size: 14px; margin: 2em; }}
+ul {{ list-style: none; margin: 0; padding-left: 1.4em; }}
+body > ul {{ padding-left: 0; }}
+summary {{ cursor: pointer; }}
+a {{ color: #0969da; text-decoration: none; }}
+a:hover {{ text-decoration: underline; }}
+.commit, .ref {{ color: #6e7781; }}
+</style>
+</head>
+<body>
+<h1><a href=\"{url}\">{title}</a> <span class=\"ref\">{git_ref}</span></h1>
+{body}<p>{folders} folders, {files} files</p>
+</body>
+</html>",
+        title = html_escape(&title),
+        url = html_escape(&format!("https://github.com/{}/{}/tree/{}", user, repo, git_ref)),
+        git_ref = html_escape(git_ref),
+        body = body,
+        folders = folders,
+        files = files,
     );
+}
 
-    println!(
-        "{}",
-        if colored {
-            summary
+fn print_tree_markdown(out: &Output, structure: &TreeNode, title: &str) {
+    let label = |name: &str, child: &TreeNode| {
+        if child.is_dir() {
+            format!("{}/", name)
         } else {
-            summary.normal().to_string()
+            name.to_string()
         }
-    );
+    };
 
-    Ok(())
+    writeln!(out, "```text");
+    writeln!(out, "{}", title);
+    print_nested(out, structure, "", &label);
+    writeln!(out, "```");
 }
 
-fn create_progress_bar(message: &str) -> ProgressBar {
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .template("{spinner:.green} [{elapsed_precise}] {msg}")
-            .unwrap()
-            .tick_chars("⠁⠂⠄⡀⢀⠠⠐⠈"),
-    );
-    pb.set_message(message.to_string());
-    pb.enable_steady_tick(Duration::from_millis(100));
-    pb
+/// Collects `(id, parent id, label)` for every folder, with the root as id 0.
+fn folder_graph(structure: &TreeNode, title: &str) -> Vec<(usize, Option<usize>, String)> {
+    fn walk(node: &TreeNode, id: usize, nodes: &mut Vec<(usize, Option<usize>, String)>) {
+        for (name, child) in node.children.iter().filter(|(_, child)| child.is_dir()) {
+            let child_id = nodes.len();
+            let files = child.children.values().filter(|c| c.item_type.as_deref() == Some("blob")).count();
+            let label = if files == 1 {
+                format!("{} (1 file)", name)
+            } else {
+                format!("{} ({} files)", name, files)
+            };
+            nodes.push((child_id, Some(id), label));
+            walk(child, child_id, nodes);
+        }
+    }
+
+    let mut nodes = vec![(0, None, title.to_string())];
+    walk(structure, 0, &mut nodes);
+    nodes
 }
 
-async fn download_file_with_retry(
-    url: &str, 
-    file_path: &Path, 
-    config: &Config, 
-    progress: &ProgressBar,
-    max_retries: u32,
-    initial_delay: Duration,
-) -> Result<()> {
-    let mut retries = 0;
-    let mut delay = initial_delay;
+fn print_tree_dot(out: &Output, structure: &TreeNode, title: &str) {
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
 
-    loop {
-        match download_file(url, file_path, config, progress).await {
-            Ok(_) => return Ok(()),
-            Err(e) => {
-                if retries >= max_retries {
-                    return Err(anyhow!("Failed after {} retries: {}", max_retries, e));
-                }
-                
-                retries += 1;
-                progress.println(format!(
-                    "Download failed, retrying ({}/{}): {}", 
-                    retries, max_retries, e
-                ));
-                
-                if e.to_string().contains("HTTP 403 Forbidden") {
-                    progress.println("403 Forbidden error. Check your token permissions and rate limits.");
-                }
-                
-                sleep(delay).await;
-                delay *= 2;
-            }
+    writeln!(out, "digraph \"{}\" {{", escape(title));
+    writeln!(out, "    rankdir=LR;");
+    writeln!(out, "    node [shape=folder, fontname=\"Helvetica\"];");
+    for (id, parent, label) in folder_graph(structure, title) {
+        writeln!(out, "    n{} [label=\"{}\"];", id, escape(&label));
+        if let Some(parent) = parent {
+            writeln!(out, "    n{} -> n{};", parent, id);
         }
     }
+    writeln!(out, "}}");
 }
 
-async fn download_file(
-    url: &str, 
-    file_path: &Path, 
-    config: &Config, 
-    progress: &ProgressBar,
-) -> Result<()> {
-    let response = config.client.get(url).send().await.context("Failed to download file")?;
+fn print_tree_mermaid(out: &Output, structure: &TreeNode, title: &str) {
+    let escape = |s: &str| s.replace('"', "#quot;");
 
-    if !response.status().is_success() {
-        return Err(anyhow!("Failed to download file: HTTP {}", response.status()));
+    writeln!(out, "graph LR");
+    for (id, parent, label) in folder_graph(structure, title) {
+        match parent {
+            Some(parent) => writeln!(out, "    n{} --> n{}[\"{}\"]", parent, id, escape(&label)),
+            None => writeln!(out, "    n{}[\"{}\"]", id, escape(&label)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum ColorWhen {
+    /// Color when writing to a terminal and NO_COLOR is not set
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorWhen {
+    /// Decides whether to color output. `main` forces `colored` on for `always`.
+    fn enabled(self) -> bool {
+        match self {
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+            ColorWhen::Auto => {
+                env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()) && std::io::stdout().is_terminal()
+            }
+        }
+    }
+}
+
+/// Guesses OSC-8 support from the environment, since terminals can't be queried for it.
+fn terminal_supports_hyperlinks() -> bool {
+    let var = |name: &str| env::var(name).unwrap_or_default();
+
+    match var("FORCE_HYPERLINK").as_str() {
+        "" => {}
+        "0" => return false,
+        _ => return true,
+    }
+
+    let term = var("TERM");
+    if term == "dumb" {
+        return false;
+    }
+
+    ["WT_SESSION", "DOMTERM", "KONSOLE_VERSION", "KITTY_WINDOW_ID", "WEZTERM_EXECUTABLE"]
+        .iter()
+        .any(|name| env::var_os(name).is_some())
+        || var("VTE_VERSION").parse::<u32>().is_ok_and(|v| v >= 5000)
+        || matches!(
+            var("TERM_PROGRAM").as_str(),
+            "iTerm.app" | "WezTerm" | "vscode" | "ghostty" | "Hyper" | "Tabby"
+        )
+        || ["kitty", "alacritty", "foot", "wezterm", "ghostty"]
+            .iter()
+            .any(|name| term.contains(name))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum IconStyle {
+    /// Emoji folder and file icons
+    Emoji,
+    /// Per file type icons from a Nerd Font
+    Nerd,
+    None,
+}
+
+/// Used when `LS_COLORS` is not set, roughly matching the `dircolors` defaults.
+const DEFAULT_LS_COLORS: &str = "di=01;34:ln=01;36:ex=01;32:\
+*.tar=01;31:*.tgz=01;31:*.gz=01;31:*.xz=01;31:*.bz2=01;31:*.zst=01;31:*.zip=01;31:*.7z=01;31:*.rar=01;31:\
+*.jpg=01;35:*.jpeg=01;35:*.png=01;35:*.gif=01;35:*.svg=01;35:*.webp=01;35:*.ico=01;35:*.mp4=01;35:*.mkv=01;35:\
+*.mp3=00;36:*.flac=00;36:*.ogg=00;36:*.wav=00;36";
+
+/// Styles from an `LS_COLORS`-style specification.
+struct LsColors {
+    types: HashMap<String, String>,
+    /// Lowercased name suffixes from `*.ext` entries
+    suffixes: Vec<(String, String)>,
+}
+
+impl LsColors {
+    fn from_env() -> Self {
+        match env::var("LS_COLORS") {
+            Ok(spec) if !spec.is_empty() => Self::parse(&spec),
+            _ => Self::parse(DEFAULT_LS_COLORS),
+        }
+    }
+
+    fn parse(spec: &str) -> Self {
+        let mut types = HashMap::new();
+        let mut suffixes = Vec::new();
+
+        for (key, value) in spec.split(':').filter_map(|entry| entry.split_once('=')) {
+            match key.strip_prefix('*') {
+                // A bare `*` would match every file
+                Some("") => {}
+                Some(suffix) => suffixes.push((suffix.to_lowercase(), value.to_string())),
+                None if key.is_empty() => {}
+                None => {
+                    types.insert(key.to_string(), value.to_string());
+                }
+            }
+        }
+
+        // Later entries win, like in ls
+        suffixes.reverse();
+        Self { types, suffixes }
+    }
+
+    /// Returns the SGR codes for an entry: symlink, directory and executable styles take
+    /// precedence over extensions, as in `ls`.
+    fn style(&self, name: &str, node: &TreeNode) -> Option<&str> {
+        let key = match (node.item_type.as_deref(), node.mode.as_deref()) {
+            (Some("tree"), _) => Some("di"),
+            (_, Some("120000")) => Some("ln"),
+            (_, Some("100755")) => Some("ex"),
+            _ => None,
+        };
+
+        let style = match key {
+            Some(key) => self.types.get(key).map(String::as_str),
+            None => {
+                let name = name.to_lowercase();
+                self.suffixes
+                    .iter()
+                    .find(|(suffix, _)| name.ends_with(suffix.as_str()))
+                    .map(|(_, style)| style.as_str())
+                    .or_else(|| self.types.get("fi").map(String::as_str))
+            }
+        };
+
+        // `ln=target` means "color like the target", which is unknown here
+        style.filter(|s| !s.is_empty() && *s != "target" && *s != "0" && *s != "00")
+    }
+
+    fn paint(&self, name: &str, node: &TreeNode) -> String {
+        // Submodules have no LS_COLORS key of their own
+        if node.item_type.as_deref() == Some("commit") {
+            return name.yellow().underline().to_string();
+        }
+
+        match self.style(name, node) {
+            Some(style) => format!("\x1b[{}m{}\x1b[0m", style, name),
+            None => name.to_string(),
+        }
+    }
+}
+
+fn tree_icon(name: &str, node: &TreeNode, style: IconStyle) -> &'static str {
+    let is_dir = node.is_dir();
+    let is_link = node.mode.as_deref() == Some("120000");
+    let is_exec = node.mode.as_deref() == Some("100755");
+    let is_submodule = node.item_type.as_deref() == Some("commit");
+
+    match style {
+        IconStyle::None => "",
+        IconStyle::Emoji if is_dir => "📁",
+        IconStyle::Emoji if is_link => "🔗",
+        IconStyle::Emoji if is_submodule => "📦",
+        IconStyle::Emoji => "📄",
+        IconStyle::Nerd if is_dir => "\u{f07b}",
+        IconStyle::Nerd if is_link => "\u{f0c1}",
+        IconStyle::Nerd if is_submodule => "\u{f1d3}",
+        IconStyle::Nerd => {
+            let lower = name.to_lowercase();
+            let ext = lower.rsplit_once('.').map_or("", |(_, ext)| ext);
+            match (lower.as_str(), ext) {
+                ("dockerfile", _) => "\u{f308}",
+                ("license" | "license.md" | "license.txt" | "copying", _) => "\u{f0219}",
+                (".gitignore" | ".gitattributes" | ".gitmodules", _) => "\u{f1d3}",
+                (_, "rs") => "\u{e7a8}",
+                (_, "py") => "\u{e73c}",
+                (_, "js" | "mjs" | "cjs") => "\u{e74e}",
+                (_, "ts" | "tsx") => "\u{e628}",
+                (_, "go") => "\u{e626}",
+                (_, "java") => "\u{e738}",
+                (_, "c") => "\u{e61e}",
+                (_, "cpp" | "cc" | "cxx" | "hpp") => "\u{e61d}",
+                (_, "h") => "\u{f0fd}",
+                (_, "rb") => "\u{e739}",
+                (_, "md" | "markdown") => "\u{f48a}",
+                (_, "json") => "\u{e60b}",
+                (_, "toml" | "yml" | "yaml" | "ini" | "cfg" | "conf") => "\u{e615}",
+                (_, "html" | "htm") => "\u{e736}",
+                (_, "css" | "scss") => "\u{e749}",
+                (_, "sh" | "bash" | "zsh" | "fish") => "\u{f489}",
+                (_, "lock") => "\u{f023}",
+                (_, "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "ico") => "\u{f1c5}",
+                (_, "zip" | "tar" | "gz" | "tgz" | "xz" | "bz2" | "zst" | "7z") => "\u{f410}",
+                (_, "txt") => "\u{f15c}",
+                _ if is_exec => "\u{f489}",
+                _ => "\u{f15b}",
+            }
+        }
+    }
+}
+
+/// Prints the tree. `links` is `(mode, user, repo, ref)` to link names to GitHub,
+/// and `changes` adds a change badge in front of every entry.
+fn print_tree_colored(
+    out: &Output,
+    structure: &TreeNode,
+    colored: bool,
+    icons: IconStyle,
+    links: Option<(LinkMode, &str, &str, &str)>,
+    changes: Option<&HashMap<String, ComparedFile>>,
+) -> Result<()> {
+    let (folder_count, file_count) = structure.counts();
+    let ls_colors = colored.then(LsColors::from_env);
+
+    // Taken from the change set rather than the displayed nodes, so folders cut off
+    // by --depth are still marked
+    let changed_dirs: HashSet<&str> = changes
+        .into_iter()
+        .flat_map(|changes| changes.keys())
+        .flat_map(|path| path.match_indices('/').map(|(i, _)| &path[..i]))
+        .collect();
+
+    let label = |name: &str, child: &TreeNode| {
+        let name_colored = match &ls_colors {
+            Some(ls_colors) => ls_colors.paint(name, child),
+            None => name.to_string(),
+        };
+        let name_colored = match links {
+            Some((mode, user, repo, git_ref)) => {
+                let is_dir = child.item_type.as_deref() != Some("blob");
+                format_link(&name_colored, &github_url(user, repo, git_ref, &child.path, is_dir), Some(mode))
+            }
+            None => name_colored,
+        };
+
+        let mut line = match tree_icon(name, child, icons) {
+            "" => name_colored,
+            icon => format!("{} {}", icon, name_colored),
+        };
+
+        if changes.is_some() {
+            // Folders get a dot when something inside them changed
+            let (badge, color) = match &child.change {
+                Some(change) => change_marker(&change.status),
+                None if changed_dirs.contains(child.path.as_str()) => ("•", Color::Yellow),
+                None => (" ", Color::White),
+            };
+            let badge = if colored {
+                badge.color(color).bold().to_string()
+            } else {
+                badge.to_string()
+            };
+            line = format!("{} {}", badge, line);
+        }
+
+        if let Some(commit) = &child.last_commit {
+            let annotation = format!(
+                "  {} {} ({})",
+                &commit.oid[..commit.oid.len().min(7)],
+                commit.message_headline,
+                commit.committed_date.get(..10).unwrap_or(&commit.committed_date)
+            );
+            if colored {
+                line.push_str(&annotation.bright_black().to_string());
+            } else {
+                line.push_str(&annotation);
+            }
+        }
+
+        line
+    };
+
+    print_nested(out, structure, "", &label);
+
+    let mut summary = format!(
+        "\nTotal folders: {}\nTotal files: {}",
+        if colored {
+            folder_count.to_string().blue().to_string()
+        } else {
+            folder_count.to_string()
+        },
+        if colored {
+            file_count.to_string().green().to_string()
+        } else {
+            file_count.to_string()
+        }
+    );
+    if let Some(changes) = changes {
+        let changed = changes.len();
+        summary.push_str(&format!(
+            "\nChanged: {}",
+            if colored {
+                changed.to_string().yellow().to_string()
+            } else {
+                changed.to_string()
+            }
+        ));
+    }
+
+    writeln!(
+        out,
+        "{}",
+        if colored {
+            summary
+        } else {
+            summary.normal().to_string()
+        }
+    );
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+struct ComparedFile {
+    filename: String,
+    status: String,
+    #[serde(default)]
+    additions: u64,
+    #[serde(default)]
+    deletions: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_filename: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CompareResponse {
+    ahead_by: u64,
+    behind_by: u64,
+    #[serde(default)]
+    files: Vec<ComparedFile>,
+}
+
+#[derive(Debug, Serialize)]
+struct TreeDiff {
+    base: String,
+    head: String,
+    ahead_by: u64,
+    behind_by: u64,
+    truncated: bool,
+    files: Vec<ComparedFile>,
+}
+
+/// Splits `base..head` (or `base...head`) into its two refs.
+fn parse_ref_range(range: &str) -> Option<(String, String)> {
+    let (base, head) = range.split_once("...").or_else(|| range.split_once(".."))?;
+    if base.is_empty() || head.is_empty() {
+        return None;
+    }
+    Some((base.to_string(), head.to_string()))
+}
+
+async fn fetch_tree_diff(
+    user: &str,
+    repo: &str,
+    base: &str,
+    head: &str,
+    config: &Config,
+    progress: &ProgressBar,
+    out: &Output,
+) -> Result<TreeDiff> {
+    let url = format!(
+        "{}/repos/{}/{}/compare/{}...{}",
+        GITHUB_API_URL, user, repo, base, head
+    );
+
+    progress.set_message(format!("Comparing {}...{}", base, head));
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .send()
+        .await
+        .context("Failed to compare refs")?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow!("Could not compare {}...{}. Check that both refs exist", base, head));
+    } else if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    let compare: CompareResponse = response
+        .json()
+        .await
+        .context("Failed to parse comparison")?;
+
+    // The compare endpoint paginates commits, not files; the file list stops at 300
+    let truncated = compare.files.len() >= COMPARE_FILE_LIMIT;
+    if truncated {
+        out.warn(format!(
+            "Warning: {}...{} changes more than {} files, only the first {} are listed",
+            base, head, COMPARE_FILE_LIMIT, COMPARE_FILE_LIMIT
+        ));
+    }
+
+    let diff = TreeDiff {
+        base: base.to_string(),
+        head: head.to_string(),
+        ahead_by: compare.ahead_by,
+        behind_by: compare.behind_by,
+        truncated,
+        files: compare.files,
+    };
+
+    progress.finish_and_clear();
+    Ok(diff)
+}
+
+/// Returns the files changed on `branch` since a ref, or since the last commit before a date.
+/// Removed files are left out since they aren't in the tree.
+async fn fetch_changes_since(
+    user: &str,
+    repo: &str,
+    branch: &str,
+    since: &str,
+    config: &Config,
+    progress: &ProgressBar,
+    out: &Output,
+) -> Result<HashMap<String, ComparedFile>> {
+    let base = match parse_timestamp(since) {
+        Ok(date) => {
+            progress.set_message(format!("Finding the last commit before {}...", since));
+            let commits = fetch_commit_log(user, repo, branch, None, (None, Some(&date)), 1, config).await?;
+            commits
+                .into_iter()
+                .next()
+                .map(|commit| commit.sha)
+                .ok_or_else(|| anyhow!("No commits on {} before {}", branch, since))?
+        }
+        Err(_) => since.to_string(),
+    };
+
+    let diff = fetch_tree_diff(user, repo, &base, branch, config, progress, out).await?;
+    Ok(diff
+        .files
+        .into_iter()
+        .filter(|file| file.status != "removed")
+        .map(|file| (file.filename.clone(), file))
+        .collect())
+}
+
+fn change_marker(status: &str) -> (&'static str, Color) {
+    match status {
+        "added" => ("A", Color::Green),
+        "removed" => ("D", Color::Red),
+        "renamed" => ("R", Color::Cyan),
+        "copied" => ("C", Color::Cyan),
+        _ => ("M", Color::Yellow),
+    }
+}
+
+fn print_tree_diff(out: &Output, diff: &TreeDiff, stat: bool, colored: bool) {
+    let mut structure = TreeNode::default();
+    for file in &diff.files {
+        let node = structure.insert(&file.filename);
+        node.item_type = Some("blob".to_string());
+        node.change = Some(file.clone());
+    }
+
+    writeln!(
+        out,
+        "Comparing {}...{}: {} commit(s) ahead, {} behind",
+        diff.base, diff.head, diff.ahead_by, diff.behind_by
+    );
+
+    let label = |name: &str, child: &TreeNode| {
+        let Some(change) = &child.change else {
+            return if colored {
+                name.blue().to_string()
+            } else {
+                name.to_string()
+            };
+        };
+
+        let (marker, color) = change_marker(&change.status);
+        let mut line = if colored {
+            format!("{} {}", marker.color(color).bold(), name.color(color))
+        } else {
+            format!("{} {}", marker, name)
+        };
+
+        if let Some(previous) = &change.previous_filename {
+            line.push_str(&format!(" ← {}", previous));
+        }
+
+        if stat {
+            let additions = format!("+{}", change.additions);
+            let deletions = format!("-{}", change.deletions);
+            if colored {
+                line.push_str(&format!("  {} {}", additions.green(), deletions.red()));
+            } else {
+                line.push_str(&format!("  {} {}", additions, deletions));
+            }
+        }
+
+        line
+    };
+
+    print_nested(out, &structure, "", &label);
+
+    let count = |status: &str| diff.files.iter().filter(|f| f.status == status).count();
+    writeln!(
+        out,
+        "\nAdded: {}, Modified: {}, Removed: {}, Renamed: {}",
+        count("added"),
+        diff.files.len() - count("added") - count("removed") - count("renamed"),
+        count("removed"),
+        count("renamed")
+    );
+
+    if stat {
+        let additions: u64 = diff.files.iter().map(|f| f.additions).sum();
+        let deletions: u64 = diff.files.iter().map(|f| f.deletions).sum();
+        writeln!(
+            out,
+            "{} file(s) changed, {} insertion(s)(+), {} deletion(s)(-)",
+            diff.files.len(),
+            additions,
+            deletions
+        );
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LastCommit {
+    oid: String,
+    message_headline: String,
+    committed_date: String,
+}
+
+/// Looks up the most recent commit touching each path, batching many
+/// `history(path:)` lookups into each GraphQL query.
+async fn fetch_last_commits(
+    user: &str,
+    repo: &str,
+    branch: &str,
+    paths: &[String],
+    config: &Config,
+    progress: &ProgressBar,
+) -> Result<HashMap<String, LastCommit>> {
+    if config.api_token.is_none() {
+        return Err(anyhow!(
+            "--last-commit uses the GraphQL API, which requires a token. Use --pat or GH_TOKEN"
+        ));
+    }
+
+    let url = format!("{}/graphql", GITHUB_API_URL);
+    let mut last_commits = HashMap::new();
+
+    for (batch_index, batch) in paths.chunks(GRAPHQL_BATCH_SIZE).enumerate() {
+        progress.set_message(format!(
+            "Fetching last commits ({}/{})...",
+            (batch_index * GRAPHQL_BATCH_SIZE + batch.len()),
+            paths.len()
+        ));
+
+        let fields: String = batch
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                format!(
+                    "p{}: history(first: 1, path: {}) {{ nodes {{ oid messageHeadline committedDate }} }}\n",
+                    i,
+                    serde_json::to_string(path).unwrap_or_default()
+                )
+            })
+            .collect();
+        let query = format!(
+            "query($owner: String!, $name: String!, $ref: String!) {{\n\
+             repository(owner: $owner, name: $name) {{\n\
+             object(expression: $ref) {{ ... on Commit {{\n{}}} }}\n\
+             }}\n\
+             }}",
+            fields
+        );
+        let body = serde_json::json!({
+            "query": query,
+            "variables": { "owner": user, "name": repo, "ref": branch },
+        });
+
+        let response = config
+            .client
+            .post(&url)
+            .headers(config.get_headers())
+            .json(&body)
+            .send()
+            .await
+            .context("Failed to query last commits")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+        }
+
+        let result: serde_json::Value = response
+            .json()
+            .await
+            .context("Failed to parse last commits")?;
+
+        if let Some(message) = result
+            .pointer("/errors/0/message")
+            .and_then(|m| m.as_str())
+        {
+            return Err(anyhow!("GitHub GraphQL error: {}", message));
+        }
+
+        let object = result
+            .pointer("/data/repository/object")
+            .ok_or_else(|| anyhow!("Ref not found: {}", branch))?;
+
+        for (i, path) in batch.iter().enumerate() {
+            let commit = object
+                .pointer(&format!("/p{}/nodes/0", i))
+                .cloned()
+                .and_then(|node| serde_json::from_value::<LastCommit>(node).ok());
+            if let Some(commit) = commit {
+                last_commits.insert(path.clone(), commit);
+            }
+        }
+    }
+
+    Ok(last_commits)
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitEntry {
+    sha: String,
+    commit: CommitDetails,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitDetails {
+    author: Option<CommitSignature>,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitSignature {
+    name: String,
+    email: String,
+    date: String,
+}
+
+/// Accepts a full ISO 8601 timestamp or a plain `YYYY-MM-DD` date.
+fn parse_timestamp(s: &str) -> std::result::Result<String, String> {
+    let s = s.trim();
+    let is_date = |d: &str| {
+        d.len() == 10
+            && d.char_indices()
+                .all(|(i, c)| if i == 4 || i == 7 { c == '-' } else { c.is_ascii_digit() })
+    };
+
+    if is_date(s) {
+        Ok(format!("{}T00:00:00Z", s))
+    } else if s.get(..10).is_some_and(is_date) && s[10..].starts_with('T') {
+        Ok(s.to_string())
+    } else {
+        Err(format!("expected YYYY-MM-DD or an ISO 8601 timestamp, got: {}", s))
+    }
+}
+
+async fn fetch_commit_log(
+    user: &str,
+    repo: &str,
+    branch: &str,
+    path: Option<&str>,
+    range: (Option<&str>, Option<&str>),
+    limit: usize,
+    config: &Config,
+) -> Result<Vec<CommitEntry>> {
+    let url = format!("{}/repos/{}/{}/commits", GITHUB_API_URL, user, repo);
+    let mut query = vec![("sha", branch)];
+    if let Some(path) = path {
+        query.push(("path", path));
+    }
+    let (since, until) = range;
+    if let Some(since) = since {
+        query.push(("since", since));
+    }
+    if let Some(until) = until {
+        query.push(("until", until));
+    }
+
+    fetch_pages(&url, &query, config, Some(limit))
+        .await
+        .context("Failed to fetch commits")
+}
+
+fn print_commit_log(out: &Output, commits: &[CommitEntry], colored: bool) {
+    for entry in commits {
+        let sha = &entry.sha[..entry.sha.len().min(7)];
+        let subject = entry.commit.message.lines().next().unwrap_or("");
+        let (author, date) = entry
+            .commit
+            .author
+            .as_ref()
+            .map(|a| (a.name.as_str(), a.date.get(..10).unwrap_or(&a.date)))
+            .unwrap_or(("unknown", ""));
+
+        if colored {
+            writeln!(
+                out,
+                "{} {} {} {}",
+                sha.yellow(),
+                date.blue(),
+                author.green(),
+                subject
+            );
+        } else {
+            writeln!(out, "{} {} {} {}", sha, date, author, subject);
+        }
+    }
+}
+
+async fn fetch_languages(user: &str, repo: &str, config: &Config) -> Result<Vec<(String, u64)>> {
+    let url = format!("{}/repos/{}/{}/languages", GITHUB_API_URL, user, repo);
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .send()
+        .await
+        .context("Failed to fetch languages")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    let languages: HashMap<String, u64> = response
+        .json()
+        .await
+        .context("Failed to parse languages")?;
+
+    let mut languages: Vec<(String, u64)> = languages.into_iter().collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    Ok(languages)
+}
+
+/// Returns the README as markdown, or `None` if the repository has none.
+async fn fetch_readme(user: &str, repo: &str, config: &Config) -> Result<Option<String>> {
+    let url = format!("{}/repos/{}/{}/readme", GITHUB_API_URL, user, repo);
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .header(reqwest::header::ACCEPT, "application/vnd.github.raw")
+        .send()
+        .await
+        .context("Failed to fetch README")?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    } else if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    Ok(Some(response.text().await.context("Failed to read README")?))
+}
+
+fn language_bar(fraction: f64, width: usize) -> String {
+    let filled = ((fraction * width as f64).round() as usize).min(width);
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
+}
+
+fn print_languages(out: &Output, languages: &[(String, u64)], colored: bool) {
+    let total: u64 = languages.iter().map(|(_, bytes)| bytes).sum();
+    if total == 0 {
+        return;
+    }
+
+    let name_width = languages.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let palette = [
+        Color::Blue,
+        Color::Green,
+        Color::Yellow,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Red,
+    ];
+
+    writeln!(out, "\nLanguages:");
+    for (i, (name, bytes)) in languages.iter().enumerate() {
+        let fraction = *bytes as f64 / total as f64;
+        let bar = language_bar(fraction, 30);
+        let bar = if colored {
+            bar.color(palette[i % palette.len()]).to_string()
+        } else {
+            bar
+        };
+        writeln!(
+            out,
+            "  {:<width$}  {} {:>5.1}%  {}",
+            name,
+            bar,
+            fraction * 100.0,
+            format_size(*bytes, BINARY),
+            width = name_width
+        );
+    }
+}
+
+fn print_repo_info(out: &Output, info: &RepoInfo, colored: bool) {
+    let heading = |text: &str| {
+        if colored {
+            text.bold().to_string()
+        } else {
+            text.to_string()
+        }
+    };
+    let field = |name: &str, value: &str| {
+        let name = format!("{:<12}", name);
+        if colored {
+            writeln!(out, "{} {}", name.blue(), value);
+        } else {
+            writeln!(out, "{} {}", name, value);
+        }
+    };
+    let date = |d: &Option<String>| {
+        d.as_deref()
+            .map(|d| d.replace('T', " ").trim_end_matches('Z').to_string())
+            .unwrap_or_else(|| "unknown".to_string())
+    };
+
+    writeln!(out, "{}", heading(info.full_name.as_deref().unwrap_or("")));
+    if let Some(description) = &info.description {
+        writeln!(out, "{}", description);
+    }
+    writeln!(out);
+
+    let mut flags = Vec::new();
+    if info.archived {
+        flags.push("archived");
+    }
+    if info.fork {
+        flags.push("fork");
+    }
+    if !flags.is_empty() {
+        let flags = flags.join(", ");
+        field(
+            "Status",
+            &if colored {
+                flags.yellow().to_string()
+            } else {
+                flags
+            },
+        );
+    }
+
+    if let Some(url) = &info.html_url {
+        field("URL", url);
+    }
+    if let Some(homepage) = info.homepage.as_deref().filter(|h| !h.is_empty()) {
+        field("Homepage", homepage);
+    }
+    field("Stars", &info.stargazers_count.to_string());
+    field("Forks", &info.forks_count.to_string());
+    field("Watchers", &info.subscribers_count.to_string());
+    field("Open issues", &info.open_issues_count.to_string());
+    field(
+        "License",
+        info.license
+            .as_ref()
+            .map(|l| l.spdx_id.as_deref().filter(|id| *id != "NOASSERTION").unwrap_or(&l.name))
+            .unwrap_or("none"),
+    );
+    if !info.topics.is_empty() {
+        field("Topics", &info.topics.join(", "));
+    }
+    field("Size", &format_size(info.size * 1024, BINARY));
+    field("Branch", &info.default_branch);
+    field("Created", &date(&info.created_at));
+    field("Last push", &date(&info.pushed_at));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RefKind {
+    Branch,
+    Tag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum RefSort {
+    Name,
+    /// Newest first
+    Date,
+}
+
+#[derive(Debug, Serialize)]
+struct RefSummary {
+    name: String,
+    sha: String,
+    date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protected: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitCommitObject {
+    sha: String,
+    tree: GitObjectRef,
+    parents: Vec<GitObjectRef>,
+    author: CommitSignature,
+    committer: CommitSignature,
+    message: String,
+    verification: Option<CommitVerification>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitObjectRef {
+    sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitVerification {
+    signature: Option<String>,
+    payload: Option<String>,
+}
+
+async fn fetch_commit_date(user: &str, repo: &str, sha: &str, config: &Config) -> Result<String> {
+    Ok(fetch_git_commit(user, repo, sha, config).await?.committer.date)
+}
+
+async fn fetch_git_commit(user: &str, repo: &str, sha: &str, config: &Config) -> Result<GitCommitObject> {
+    let url = format!("{}/repos/{}/{}/git/commits/{}", GITHUB_API_URL, user, repo, sha);
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .send()
+        .await
+        .context("Failed to fetch commit")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    response.json().await.context("Failed to parse commit")
+}
+
+/// Lists all branches or tags matching `pattern`, without their commit dates.
+async fn fetch_refs(
+    user: &str,
+    repo: &str,
+    kind: RefKind,
+    pattern: Option<&globset::GlobMatcher>,
+    config: &Config,
+    progress: &ProgressBar,
+) -> Result<Vec<RefSummary>> {
+    let (endpoint, label) = match kind {
+        RefKind::Branch => ("branches", "branches"),
+        RefKind::Tag => ("tags", "tags"),
+    };
+
+    progress.set_message(format!("Fetching {}...", label));
+    let url = format!("{}/repos/{}/{}/{}", GITHUB_API_URL, user, repo, endpoint);
+    let refs: Vec<Branch> = fetch_all_pages(&url, config)
+        .await
+        .with_context(|| format!("Failed to fetch {}", label))?;
+
+    Ok(refs
+        .into_iter()
+        .filter(|r| pattern.is_none_or(|p| p.is_match(&r.name)))
+        .map(|r| RefSummary {
+            date: None,
+            protected: (kind == RefKind::Branch).then_some(r.protected),
+            name: r.name,
+            sha: r.commit.sha,
+        })
+        .collect())
+}
+
+/// Fills in commit dates with one request per commit, for when there's no token for GraphQL.
+async fn fetch_ref_dates(
+    user: &str,
+    repo: &str,
+    refs: &mut [RefSummary],
+    config: &Config,
+    progress: &ProgressBar,
+    out: &Output,
+) {
+    // Several refs often point at the same commit
+    let shas: HashSet<&str> = refs.iter().map(|r| r.sha.as_str()).collect();
+    progress.set_message(format!("Fetching {} commit date(s)...", shas.len()));
+
+    let fetches = shas
+        .into_iter()
+        .map(|sha| async move { (sha.to_string(), fetch_commit_date(user, repo, sha, config).await) });
+    let results: Vec<(String, Result<String>)> =
+        futures::StreamExt::buffer_unordered(futures::stream::iter(fetches), MAX_CONCURRENT_REQUESTS)
+            .collect()
+            .await;
+
+    // One failed lookup shouldn't hide the whole listing; its date is shown as unknown
+    let mut dates = HashMap::new();
+    let mut failures = Vec::new();
+    for (sha, date) in results {
+        match date {
+            Ok(date) => {
+                dates.insert(sha, date);
+            }
+            Err(e) => failures.push(e),
+        }
+    }
+    if let Some(error) = failures.first() {
+        out.warn(format!(
+            "Warning: Could not fetch {} commit date(s), shown as unknown: {:#}",
+            failures.len(),
+            error
+        ));
+    }
+
+    for r in refs {
+        r.date = dates.get(&r.sha).cloned();
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlRefPage {
+    #[serde(rename = "pageInfo")]
+    page_info: GraphqlPageInfo,
+    nodes: Vec<GraphqlRef>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlPageInfo {
+    has_next_page: bool,
+    end_cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphqlRef {
+    name: String,
+    target: GraphqlRefTarget,
+}
+
+/// A commit, or an annotated tag pointing at one.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphqlRefTarget {
+    oid: String,
+    committed_date: Option<String>,
+    target: Option<Box<GraphqlRefTarget>>,
+}
+
+/// Lists all branches or tags with their commit dates, 100 per GraphQL query.
+/// Branches are not marked as protected.
+async fn fetch_refs_with_dates(
+    user: &str,
+    repo: &str,
+    kind: RefKind,
+    config: &Config,
+    progress: &ProgressBar,
+) -> Result<Vec<RefSummary>> {
+    let (prefix, label) = match kind {
+        RefKind::Branch => ("refs/heads/", "branches"),
+        RefKind::Tag => ("refs/tags/", "tags"),
+    };
+    let query = "query($owner: String!, $name: String!, $prefix: String!, $after: String) {
+        repository(owner: $owner, name: $name) {
+            refs(refPrefix: $prefix, first: 100, after: $after) {
+                pageInfo { hasNextPage endCursor }
+                nodes {
+                    name
+                    target {
+                        oid
+                        ... on Commit { committedDate }
+                        ... on Tag { target { oid ... on Commit { committedDate } } }
+                    }
+                }
+            }
+        }
+    }";
+
+    let url = format!("{}/graphql", GITHUB_API_URL);
+    let mut refs = Vec::new();
+    let mut after: Option<String> = None;
+
+    loop {
+        progress.set_message(format!("Fetching {} ({})...", label, refs.len()));
+        let body = serde_json::json!({
+            "query": query,
+            "variables": { "owner": user, "name": repo, "prefix": prefix, "after": after },
+        });
+
+        let response = config
+            .client
+            .post(&url)
+            .headers(config.get_headers())
+            .json(&body)
+            .send()
+            .await
+            .with_context(|| format!("Failed to fetch {}", label))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let error_body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+        }
+
+        let result: serde_json::Value = response
+            .json()
+            .await
+            .with_context(|| format!("Failed to parse {}", label))?;
+
+        if let Some(message) = result
+            .pointer("/errors/0/message")
+            .and_then(|m| m.as_str())
+        {
+            return Err(anyhow!("GitHub GraphQL error: {}", message));
+        }
+
+        let page = result
+            .pointer("/data/repository/refs")
+            .filter(|page| !page.is_null())
+            .ok_or_else(|| anyhow!("Repository not found: {}/{}", user, repo))?;
+        let page: GraphqlRefPage =
+            serde_json::from_value(page.clone()).with_context(|| format!("Failed to parse {}", label))?;
+
+        for r in page.nodes {
+            // Annotated tags point at a tag object, which points at the commit
+            let commit = match r.target.target {
+                Some(target) => *target,
+                None => r.target,
+            };
+            refs.push(RefSummary {
+                name: r.name,
+                sha: commit.oid,
+                date: commit.committed_date,
+                protected: None,
+            });
+        }
+
+        match page.page_info {
+            GraphqlPageInfo { has_next_page: true, end_cursor: Some(cursor) } => after = Some(cursor),
+            _ => break,
+        }
+    }
+
+    Ok(refs)
+}
+
+fn print_refs(out: &Output, refs: &[RefSummary], format: OutputFormat, colored: bool) -> Result<()> {
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(refs)?),
+        OutputFormat::Paths => {
+            for r in refs {
+                writeln!(out, "{}", r.name);
+            }
+        }
+        OutputFormat::Text => {
+            let name_width = refs.iter().map(|r| r.name.len()).max().unwrap_or(0);
+            for r in refs {
+                let name = format!("{:<width$}", r.name, width = name_width);
+                let sha = &r.sha[..r.sha.len().min(7)];
+                let date = r
+                    .date
+                    .as_deref()
+                    .map(|d| d.replace('T', " ").trim_end_matches('Z').to_string())
+                    .unwrap_or_else(|| format!("{:<19}", "unknown"));
+                let protected = if r.protected == Some(true) { "protected" } else { "" };
+
+                if colored {
+                    writeln!(
+                        out,
+                        "{}  {}  {}  {}",
+                        name.green(),
+                        sha.yellow(),
+                        date.blue(),
+                        protected.red()
+                    );
+                } else {
+                    writeln!(out, "{}  {}  {}  {}", name, sha, date, protected);
+                }
+            }
+
+            writeln!(out, "\nTotal: {}", refs.len());
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists matching refs, looking up commit dates only when `with_dates` is set.
+async fn list_refs(
+    repo: Option<&str>,
+    kind: RefKind,
+    pattern: Option<&str>,
+    sort: RefSort,
+    with_dates: bool,
+    config: &Config,
+    out: &Output,
+) -> Result<Vec<RefSummary>> {
+    let (user, repo_name) = parse_repo_arg(repo)?;
+
+    let pattern = pattern
+        .map(|p| {
+            globset::Glob::new(p)
+                .map(|g| g.compile_matcher())
+                .with_context(|| format!("Invalid glob: {}", p))
+        })
+        .transpose()?;
+
+    let progress = out.progress_bar("Fetching refs...");
+    let mut refs = if with_dates && config.api_token.is_some() {
+        let mut refs = fetch_refs_with_dates(&user, &repo_name, kind, config, &progress).await?;
+        refs.retain(|r| pattern.as_ref().is_none_or(|p| p.is_match(&r.name)));
+
+        if kind == RefKind::Branch {
+            // Usually a single page, unlike the full branch list
+            let url = format!("{}/repos/{}/{}/branches?protected=true", GITHUB_API_URL, user, repo_name);
+            let protected: Vec<Branch> = fetch_all_pages(&url, config)
+                .await
+                .context("Failed to fetch protected branches")?;
+            let protected: HashSet<String> = protected.into_iter().map(|b| b.name).collect();
+            for r in &mut refs {
+                r.protected = Some(protected.contains(&r.name));
+            }
+        }
+        refs
+    } else {
+        let mut refs = fetch_refs(&user, &repo_name, kind, pattern.as_ref(), config, &progress).await?;
+        if with_dates {
+            fetch_ref_dates(&user, &repo_name, &mut refs, config, &progress, out).await;
+        }
+        refs
+    };
+    progress.finish_and_clear();
+
+    match sort {
+        RefSort::Name => refs.sort_by(|a, b| a.name.cmp(&b.name)),
+        // ISO 8601 timestamps sort lexically
+        RefSort::Date => refs.sort_by(|a, b| b.date.cmp(&a.date)),
+    }
+
+    Ok(refs)
+}
+
+#[derive(Debug, Deserialize)]
+struct GitHubAccount {
+    login: String,
+    #[serde(rename = "type")]
+    account_type: String,
+}
+
+async fn fetch_account(url: &str, config: &Config) -> Result<GitHubAccount> {
+    let response = config
+        .client
+        .get(url)
+        .headers(config.get_headers())
+        .send()
+        .await
+        .context("Failed to fetch account")?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow!("User or organization not found"));
+    } else if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    response.json().await.context("Failed to parse account")
+}
+
+/// Lists every repository of a user or organization, including private ones
+/// the token can see.
+async fn fetch_owner_repos(owner: &str, config: &Config, progress: &ProgressBar) -> Result<Vec<RepoInfo>> {
+    let account = fetch_account(&format!("{}/users/{}", GITHUB_API_URL, owner), config).await?;
+
+    // `/users/{owner}/repos` only ever lists public repositories
+    let url = if account.account_type == "Organization" {
+        format!("{}/orgs/{}/repos?type=all", GITHUB_API_URL, account.login)
+    } else {
+        let is_self = if config.api_token.is_some() {
+            fetch_account(&format!("{}/user", GITHUB_API_URL), config)
+                .await
+                .is_ok_and(|me| me.login.eq_ignore_ascii_case(&account.login))
+        } else {
+            false
+        };
+
+        if is_self {
+            format!("{}/user/repos?affiliation=owner&visibility=all", GITHUB_API_URL)
+        } else {
+            format!("{}/users/{}/repos?type=owner", GITHUB_API_URL, account.login)
+        }
+    };
+
+    progress.set_message(format!("Fetching repositories of {}...", account.login));
+    fetch_all_pages(&url, config)
+        .await
+        .context("Failed to fetch repositories")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum RepoSort {
+    Name,
+    Stars,
+    Size,
+    /// Most recently pushed first
+    Pushed,
+}
+
+fn print_repos(out: &Output, repos: &[RepoInfo], format: OutputFormat, colored: bool) -> Result<()> {
+    match format {
+        OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(repos)?),
+        OutputFormat::Paths => {
+            for r in repos {
+                writeln!(out, "{}", r.full_name.as_deref().unwrap_or(&r.name));
+            }
+        }
+        OutputFormat::Text => {
+            let name_width = repos
+                .iter()
+                .map(|r| r.full_name.as_deref().unwrap_or(&r.name).len())
+                .max()
+                .unwrap_or(0);
+
+            for r in repos {
+                let name = format!(
+                    "{:<width$}",
+                    r.full_name.as_deref().unwrap_or(&r.name),
+                    width = name_width
+                );
+                let language = format!("{:<12}", r.language.as_deref().unwrap_or("-"));
+                let size = format!("{:>10}", format_size(r.size * 1024, BINARY));
+                let stars = format!("★ {:<6}", r.stargazers_count);
+                let pushed = r.pushed_at.as_deref().and_then(|d| d.get(..10)).unwrap_or("");
+
+                let mut flags = Vec::new();
+                if r.private {
+                    flags.push("private");
+                }
+                if r.fork {
+                    flags.push("fork");
+                }
+                if r.archived {
+                    flags.push("archived");
+                }
+                let flags = flags.join(",");
+
+                if colored {
+                    writeln!(
+                        out,
+                        "{}  {}  {}  {}  {}  {}",
+                        name.green(),
+                        language.cyan(),
+                        size,
+                        stars.yellow(),
+                        pushed.blue(),
+                        flags.red()
+                    );
+                } else {
+                    writeln!(
+                        out,
+                        "{}  {}  {}  {}  {}  {}",
+                        name, language, size, stars, pushed, flags
+                    );
+                }
+            }
+
+            writeln!(out, "\nTotal repositories: {}", repos.len());
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Default)]
+struct GroupStats {
+    files: u64,
+    bytes: u64,
+}
+
+struct TreeStats {
+    by_extension: BTreeMap<String, GroupStats>,
+    by_directory: BTreeMap<String, GroupStats>,
+    files: u64,
+    bytes: u64,
+}
+
+fn collect_tree_stats(tree_items: &[TreeItem]) -> TreeStats {
+    let mut stats = TreeStats {
+        by_extension: BTreeMap::new(),
+        by_directory: BTreeMap::new(),
+        files: 0,
+        bytes: 0,
+    };
+
+    for item in tree_items.iter().filter(|item| item.item_type == "blob") {
+        let size = item.size.unwrap_or(0);
+        let name = item.path.rsplit('/').next().unwrap_or(&item.path);
+        let extension = match name.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => format!(".{}", ext.to_lowercase()),
+            _ => "(none)".to_string(),
+        };
+        let directory = match item.path.split_once('/') {
+            Some((dir, _)) => format!("{}/", dir),
+            None => "(root)".to_string(),
+        };
+
+        for group in [
+            stats.by_extension.entry(extension).or_default(),
+            stats.by_directory.entry(directory).or_default(),
+        ] {
+            group.files += 1;
+            group.bytes += size;
+        }
+        stats.files += 1;
+        stats.bytes += size;
+    }
+
+    stats
+}
+
+fn print_stats_table(
+    out: &Output,
+    title: &str,
+    groups: &BTreeMap<String, GroupStats>,
+    totals: (u64, u64),
+    top: Option<usize>,
+    colored: bool,
+) {
+    let (total_files, total_bytes) = totals;
+    let percent = |part: u64, total: u64| {
+        if total == 0 {
+            0.0
+        } else {
+            part as f64 * 100.0 / total as f64
+        }
+    };
+
+    let mut rows: Vec<_> = groups.iter().collect();
+    rows.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(b.0)));
+    let hidden = top.map(|top| rows.len().saturating_sub(top)).unwrap_or(0);
+    rows.truncate(rows.len() - hidden);
+
+    let width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0).max(title.len());
+    let header = format!(
+        "{:<width$}  {:>8}  {:>6}  {:>10}  {:>6}",
+        title,
+        "Files",
+        "%",
+        "Size",
+        "%",
+        width = width
+    );
+    writeln!(out, "{}", if colored { header.bold().to_string() } else { header });
+
+    for (name, group) in rows {
+        let name = format!("{:<width$}", name, width = width);
+        writeln!(
+            out,
+            "{}  {:>8}  {:>5.1}%  {:>10}  {:>5.1}%",
+            if colored { name.blue().to_string() } else { name },
+            group.files,
+            percent(group.files, total_files),
+            format_size(group.bytes, BINARY),
+            percent(group.bytes, total_bytes)
+        );
+    }
+
+    if hidden > 0 {
+        writeln!(out, "... and {} more", hidden);
+    }
+}
+
+fn print_tree_stats(out: &Output, stats: &TreeStats, top: Option<usize>, colored: bool) {
+    let totals = (stats.files, stats.bytes);
+    print_stats_table(out, "Extension", &stats.by_extension, totals, top, colored);
+    writeln!(out);
+    print_stats_table(out, "Directory", &stats.by_directory, totals, top, colored);
+    writeln!(
+        out,
+        "\nTotal files: {}\nTotal size: {}",
+        stats.files,
+        format_size(stats.bytes, BINARY)
+    );
+}
+
+/// Hashes a local file the way git hashes blobs. Symlinks hash their target path.
+fn git_blob_sha(path: &Path) -> Result<String> {
+    use sha1::{Digest, Sha1};
+    use std::io::Read;
+
+    let metadata = fs::symlink_metadata(path)
+        .with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+
+    let mut hasher = Sha1::new();
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(path)
+            .with_context(|| format!("Failed to read link: {}", path.display()))?;
+        let target = target.to_string_lossy();
+        hasher.update(format!("blob {}\0", target.len()).as_bytes());
+        hasher.update(target.as_bytes());
+    } else {
+        let mut file = fs::File::open(path)
+            .with_context(|| format!("Failed to open file: {}", path.display()))?;
+        hasher.update(format!("blob {}\0", metadata.len()).as_bytes());
+
+        let mut buffer = [0u8; 64 * 1024];
+        loop {
+            let read = file
+                .read(&mut buffer)
+                .with_context(|| format!("Failed to read file: {}", path.display()))?;
+            if read == 0 {
+                break;
+            }
+            hasher.update(&buffer[..read]);
+        }
+    }
+
+    Ok(to_hex(&hasher.finalize()))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Result<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return Err(anyhow!("Invalid object id: {}", hex));
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| anyhow!("Invalid object id: {}", hex)))
+        .collect()
+}
+
+/// Lists files below `dir` as `/`-separated paths relative to it, skipping `.git` and placeholder indexes.
+fn collect_local_files(dir: &Path) -> Result<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current)
+            .with_context(|| format!("Failed to read directory: {}", current.display()))?;
+
+        for entry in entries {
+            let entry = entry.context("Failed to read directory entry")?;
+            let path = entry.path();
+            let file_type = entry.file_type().context("Failed to read file type")?;
+
+            if file_type.is_dir() {
+                if entry.file_name() != ".git" {
+                    pending.push(path);
+                }
+                continue;
+            }
+            if entry.file_name() == PLACEHOLDER_INDEX {
+                continue;
+            }
+
+            let relative = path
+                .strip_prefix(dir)
+                .unwrap_or(&path)
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.insert(relative, path);
+        }
+    }
+
+    Ok(files)
+}
+
+/// Lists directories below `dir` (excluding `dir` itself and `.git`), deepest first.
+fn collect_local_dirs(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current)
+            .with_context(|| format!("Failed to read directory: {}", current.display()))?;
+
+        for entry in entries {
+            let entry = entry.context("Failed to read directory entry")?;
+            if entry.file_type().context("Failed to read file type")?.is_dir() && entry.file_name() != ".git" {
+                dirs.push(entry.path());
+                pending.push(entry.path());
+            }
+        }
+    }
+
+    dirs.sort_by_key(|d| std::cmp::Reverse(d.components().count()));
+    Ok(dirs)
+}
+
+/// Returned by `status` when the directory differs, so `main` can exit with code 2.
+#[derive(Debug)]
+struct OutOfDate;
+
+impl std::fmt::Display for OutOfDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Local directory differs from the remote tree")
+    }
+}
+
+impl std::error::Error for OutOfDate {}
+
+#[derive(Default)]
+struct LocalStatus {
+    identical: Vec<String>,
+    modified: Vec<String>,
+    missing: Vec<String>,
+    extra: Vec<String>,
+}
+
+impl LocalStatus {
+    fn is_clean(&self, ignore_extra: bool) -> bool {
+        self.modified.is_empty()
+            && self.missing.is_empty()
+            && (ignore_extra || self.extra.is_empty())
+    }
+}
+
+fn compare_local_tree(
+    tree_items: &[TreeItem],
+    subpath: Option<&str>,
+    local_dir: &Path,
+    progress: &ProgressBar,
+) -> Result<LocalStatus> {
+    let prefix = subpath
+        .map(|p| p.trim_matches('/'))
+        .filter(|p| !p.is_empty())
+        .map(|p| format!("{}/", p));
+
+    // Submodules have no blob to compare against
+    let remote: BTreeMap<&str, &str> = tree_items
+        .iter()
+        .filter(|item| item.item_type == "blob")
+        .filter_map(|item| match &prefix {
+            Some(prefix) => item
+                .path
+                .strip_prefix(prefix.as_str())
+                .map(|p| (p, item.sha.as_str())),
+            None => Some((item.path.as_str(), item.sha.as_str())),
+        })
+        .collect();
+
+    let mut local = collect_local_files(local_dir)?;
+    let mut status = LocalStatus::default();
+
+    for (path, sha) in remote {
+        match local.remove(path) {
+            None => status.missing.push(path.to_string()),
+            Some(local_path) => {
+                progress.set_message(format!("Hashing: {}", path));
+                if git_blob_sha(&local_path)? == sha {
+                    status.identical.push(path.to_string());
+                } else {
+                    status.modified.push(path.to_string());
+                }
+            }
+        }
+    }
+    status.extra = local.into_keys().collect();
+
+    Ok(status)
+}
+
+fn print_local_status(out: &Output, status: &LocalStatus, show_identical: bool, colored: bool) {
+    let mut groups = vec![
+        ("modified", &status.modified, Color::Yellow),
+        ("missing", &status.missing, Color::Red),
+        ("extra", &status.extra, Color::Cyan),
+    ];
+    if show_identical {
+        groups.push(("identical", &status.identical, Color::Green));
+    }
+
+    for (label, paths, color) in groups {
+        for path in paths {
+            let label = format!("{:>10}:", label);
+            if colored {
+                writeln!(out, "{} {}", label.color(color), path);
+            } else {
+                writeln!(out, "{} {}", label, path);
+            }
+        }
+    }
+
+    writeln!(
+        out,
+        "\nIdentical: {}, Modified: {}, Missing: {}, Extra: {}",
+        status.identical.len(),
+        status.modified.len(),
+        status.missing.len(),
+        status.extra.len()
+    );
+}
+
+fn git_object_id(kind: &str, content: &[u8]) -> String {
+    use sha1::{Digest, Sha1};
+
+    let mut hasher = Sha1::new();
+    hasher.update(format!("{} {}\0", kind, content.len()).as_bytes());
+    hasher.update(content);
+    to_hex(&hasher.finalize())
+}
+
+/// Writes a zlib-compressed loose object into `git_dir` and returns its id.
+fn write_loose_object(git_dir: &Path, kind: &str, content: &[u8]) -> Result<String> {
+    use flate2::{write::ZlibEncoder, Compression};
+    use std::io::Write;
+
+    let id = git_object_id(kind, content);
+    let object_dir = git_dir.join("objects").join(&id[..2]);
+    let object_path = object_dir.join(&id[2..]);
+    if object_path.exists() {
+        return Ok(id);
+    }
+
+    fs::create_dir_all(&object_dir).context("Failed to create object directory")?;
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(format!("{} {}\0", kind, content.len()).as_bytes())?;
+    encoder.write_all(content)?;
+    let compressed = encoder.finish().context("Failed to compress object")?;
+    fs::write(&object_path, compressed)
+        .with_context(|| format!("Failed to write object: {}", object_path.display()))?;
+
+    Ok(id)
+}
+
+/// Parses the UTC timestamps GitHub returns (`2024-01-31T12:00:00Z`) into seconds since the epoch.
+fn unix_time(date: &str) -> Option<i64> {
+    let date = date.strip_suffix('Z').or_else(|| date.strip_suffix("+00:00"))?;
+    let (day, time) = date.split_once('T')?;
+
+    let mut day = day.splitn(3, '-').map(|p| p.parse::<i64>().ok());
+    let (y, m, d) = (day.next()??, day.next()??, day.next()??);
+    let mut time = time.splitn(3, ':').map(|p| p.parse::<i64>().ok());
+    let (hh, mm, ss) = (time.next()??, time.next()??, time.next()??);
+
+    // Days since the epoch for a proleptic Gregorian date
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+
+    Some(days * 86400 + hh * 3600 + mm * 60 + ss)
+}
+
+/// Formats seconds since the epoch as a UTC timestamp, the inverse of `unix_time`.
+fn format_unix_time(secs: i64) -> String {
+    let (days, rem) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        y,
+        m,
+        d,
+        rem / 3600,
+        rem % 3600 / 60,
+        rem % 60
+    )
+}
+
+fn format_tz_offset(minutes: i32) -> String {
+    let sign = if minutes < 0 { '-' } else { '+' };
+    format!("{}{:02}{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
+
+fn commit_object(commit: &GitCommitObject, author_time: (i64, i32), committer_time: (i64, i32), message: &str) -> Vec<u8> {
+    let mut raw = format!("tree {}\n", commit.tree.sha);
+    for parent in &commit.parents {
+        raw.push_str(&format!("parent {}\n", parent.sha));
+    }
+    raw.push_str(&format!(
+        "author {} <{}> {} {}\n",
+        commit.author.name,
+        commit.author.email,
+        author_time.0,
+        format_tz_offset(author_time.1)
+    ));
+    raw.push_str(&format!(
+        "committer {} <{}> {} {}\n",
+        commit.committer.name,
+        commit.committer.email,
+        committer_time.0,
+        format_tz_offset(committer_time.1)
+    ));
+    raw.push('\n');
+    raw.push_str(message);
+    raw.into_bytes()
+}
+
+/// Rebuilds the exact bytes of a commit object so it keeps its original id.
+///
+/// The API drops the timezones, so every plausible author/committer offset pair is tried
+/// until the hash matches. Signed commits are rebuilt from the verification payload.
+fn reconstruct_commit(commit: &GitCommitObject) -> Option<Vec<u8>> {
+    if let Some(verification) = &commit.verification
+        && let (Some(payload), Some(signature)) = (&verification.payload, &verification.signature)
+        && let Some(header_end) = payload.find("\n\n")
+    {
+        let signature = signature.trim_end_matches('\n').replace('\n', "\n ");
+        let raw = format!(
+            "{}gpgsig {}\n{}",
+            &payload[..header_end + 1],
+            signature,
+            &payload[header_end + 1..]
+        )
+        .into_bytes();
+        if git_object_id("commit", &raw) == commit.sha {
+            return Some(raw);
+        }
+    }
+
+    let author_time = unix_time(&commit.author.date)?;
+    let committer_time = unix_time(&commit.committer.date)?;
+    let offsets: Vec<i32> = (-48..=56).map(|q| q * 15).collect();
+    let messages = [commit.message.clone(), format!("{}\n", commit.message)];
+
+    // Author and committer usually share a timezone, so try those pairs first
+    let same = offsets.iter().map(|&o| (o, o));
+    let mixed = offsets
+        .iter()
+        .flat_map(|&a| offsets.iter().map(move |&c| (a, c)))
+        .filter(|(a, c)| a != c);
+
+    for (author_tz, committer_tz) in same.chain(mixed) {
+        for message in &messages {
+            let raw = commit_object(commit, (author_time, author_tz), (committer_time, committer_tz), message);
+            if git_object_id("commit", &raw) == commit.sha {
+                return Some(raw);
+            }
+        }
+    }
+
+    None
+}
+
+/// Serializes the directory trees of a recursive listing, checking each against its id.
+fn write_tree_objects(git_dir: &Path, root_sha: &str, tree_items: &[TreeItem]) -> Result<()> {
+    let mut trees: BTreeMap<&str, Vec<&TreeItem>> = BTreeMap::new();
+    trees.insert("", Vec::new());
+    for item in tree_items {
+        if item.item_type == "tree" {
+            trees.entry(item.path.as_str()).or_default();
+        }
+        let parent = item.path.rsplit_once('/').map_or("", |(parent, _)| parent);
+        trees.entry(parent).or_default().push(item);
+    }
+
+    let tree_ids: HashMap<&str, &str> = tree_items
+        .iter()
+        .filter(|item| item.item_type == "tree")
+        .map(|item| (item.path.as_str(), item.sha.as_str()))
+        .chain(std::iter::once(("", root_sha)))
+        .collect();
+
+    for (path, mut entries) in trees {
+        let name = |item: &TreeItem| item.path.rsplit('/').next().unwrap_or("").to_string();
+        // Git orders tree entries as if directory names ended with '/'
+        entries.sort_by_key(|item| {
+            let mut key = name(item);
+            if item.item_type == "tree" {
+                key.push('/');
+            }
+            key
+        });
+
+        let mut content = Vec::new();
+        for item in entries {
+            content.extend_from_slice(item.mode.trim_start_matches('0').as_bytes());
+            content.push(b' ');
+            content.extend_from_slice(name(item).as_bytes());
+            content.push(0);
+            content.extend_from_slice(&from_hex(&item.sha)?);
+        }
+
+        let id = write_loose_object(git_dir, "tree", &content)?;
+        if Some(&id.as_str()) != tree_ids.get(path) {
+            return Err(anyhow!("Rebuilt tree '{}' does not match the remote tree", path));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn index_stat(path: &Path) -> Result<[u32; 9]> {
+    use std::os::unix::fs::MetadataExt;
+
+    let m = fs::symlink_metadata(path).with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+    Ok([
+        m.ctime() as u32,
+        m.ctime_nsec() as u32,
+        m.mtime() as u32,
+        m.mtime_nsec() as u32,
+        m.dev() as u32,
+        m.ino() as u32,
+        m.uid(),
+        m.gid(),
+        m.size() as u32,
+    ])
+}
+
+#[cfg(not(unix))]
+fn index_stat(path: &Path) -> Result<[u32; 9]> {
+    let m = fs::symlink_metadata(path).with_context(|| format!("Failed to read metadata: {}", path.display()))?;
+    Ok([0, 0, 0, 0, 0, 0, 0, 0, m.len() as u32])
+}
+
+/// Writes a version 2 index describing the checked-out files.
+fn write_git_index(git_dir: &Path, work_dir: &Path, tree_items: &[TreeItem]) -> Result<()> {
+    use sha1::{Digest, Sha1};
+
+    let mut entries: Vec<&TreeItem> = tree_items.iter().filter(|item| item.item_type != "tree").collect();
+    entries.sort_by(|a, b| a.path.as_bytes().cmp(b.path.as_bytes()));
+
+    let mut data = Vec::new();
+    data.extend_from_slice(b"DIRC");
+    data.extend_from_slice(&2u32.to_be_bytes());
+    data.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+
+    for item in entries {
+        let mode = u32::from_str_radix(&item.mode, 8).with_context(|| format!("Invalid mode: {}", item.mode))?;
+        // Submodules are recorded by commit id only
+        let stat = if item.item_type == "commit" {
+            [0; 9]
+        } else {
+            index_stat(&work_dir.join(&item.path))?
+        };
+
+        let start = data.len();
+        for value in &stat[..6] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&mode.to_be_bytes());
+        for value in &stat[6..] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        data.extend_from_slice(&from_hex(&item.sha)?);
+        data.extend_from_slice(&(item.path.len().min(0xFFF) as u16).to_be_bytes());
+        data.extend_from_slice(item.path.as_bytes());
+
+        // Entries are NUL-padded to a multiple of 8 bytes, with at least one NUL
+        let padding = 8 - (data.len() - start) % 8;
+        data.extend(std::iter::repeat_n(0u8, padding));
+    }
+
+    let checksum = Sha1::digest(&data);
+    data.extend_from_slice(&checksum);
+    fs::write(git_dir.join("index"), data).context("Failed to write index")
+}
+
+fn write_work_file(path: &Path, mode: &str, content: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).context("Failed to create parent directory")?;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if mode == "120000" {
+            let target = String::from_utf8_lossy(content).into_owned();
+            std::os::unix::fs::symlink(target, path)
+                .with_context(|| format!("Failed to create symlink: {}", path.display()))?;
+            return Ok(());
+        }
+
+        fs::write(path, content).with_context(|| format!("Failed to write file: {}", path.display()))?;
+        if mode == "100755" {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("Failed to set permissions: {}", path.display()))?;
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = mode;
+        fs::write(path, content).with_context(|| format!("Failed to write file: {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Returns the head commit of `branch`, or `None` if it isn't a branch.
+async fn fetch_branch_head(user: &str, repo: &str, branch: &str, config: &Config) -> Result<Option<String>> {
+    let url = format!("{}/repos/{}/{}/branches/{}", GITHUB_API_URL, user, repo, branch);
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .send()
+        .await
+        .context("Failed to fetch branch")?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    } else if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    let branch: Branch = response.json().await.context("Failed to parse branch")?;
+    Ok(Some(branch.commit.sha))
+}
+
+async fn fetch_commit_sha(user: &str, repo: &str, git_ref: &str, config: &Config) -> Result<String> {
+    let url = format!("{}/repos/{}/{}/commits/{}", GITHUB_API_URL, user, repo, git_ref);
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .header(reqwest::header::ACCEPT, "application/vnd.github.sha")
+        .send()
+        .await
+        .context("Failed to resolve ref")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    Ok(response.text().await.context("Failed to read commit id")?.trim().to_string())
+}
+
+async fn fetch_blob(user: &str, repo: &str, sha: &str, config: &Config) -> Result<Vec<u8>> {
+    let url = format!("{}/repos/{}/{}/git/blobs/{}", GITHUB_API_URL, user, repo, sha);
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .header(reqwest::header::ACCEPT, "application/vnd.github.raw")
+        .send()
+        .await
+        .context("Failed to fetch blob")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    Ok(response.bytes().await.context("Failed to read blob")?.to_vec())
+}
+
+/// Builds a depth-1 git repository in `target` from the REST API, without a git binary.
+async fn shallow_clone(
+    user: &str,
+    repo: &str,
+    git_ref: &str,
+    target: &Path,
+    config: &Config,
+    progress: &ProgressBar,
+    out: &Output,
+) -> Result<()> {
+    progress.set_message("Resolving ref...");
+    let branch_head = fetch_branch_head(user, repo, git_ref, config).await?;
+    let head_sha = match &branch_head {
+        Some(sha) => sha.clone(),
+        None => fetch_commit_sha(user, repo, git_ref, config).await?,
+    };
+
+    progress.set_message("Fetching commit...");
+    let commit = fetch_git_commit(user, repo, &head_sha, config).await?;
+
+    progress.set_message("Fetching tree data...");
+    let tree = fetch_tree(user, repo, &commit.tree.sha, config, out).await?;
+    if tree.truncated {
+        return Err(anyhow!("Repository tree is too large to clone through the API"));
+    }
+
+    let git_dir = target.join(".git");
+    for dir in ["objects/info", "objects/pack", "refs/heads", "refs/tags"] {
+        fs::create_dir_all(git_dir.join(dir)).context("Failed to create .git directory")?;
+    }
+
+    progress.set_message("Writing trees...");
+    write_tree_objects(&git_dir, &tree.sha, &tree.tree)?;
+    for item in &tree.tree {
+        if item.item_type != "blob" {
+            fs::create_dir_all(target.join(&item.path)).context("Failed to create directory")?;
+        }
+    }
+
+    let mut blobs: HashMap<&str, Vec<&TreeItem>> = HashMap::new();
+    for item in tree.tree.iter().filter(|item| item.item_type == "blob") {
+        blobs.entry(item.sha.as_str()).or_default().push(item);
+    }
+
+    let total = blobs.len();
+    let fetches = blobs
+        .keys()
+        .map(|&sha| async move { fetch_blob(user, repo, sha, config).await.map(|content| (sha, content)) });
+    let mut stream = futures::StreamExt::buffer_unordered(futures::stream::iter(fetches), MAX_CONCURRENT_REQUESTS);
+
+    let mut fetched = 0;
+    while let Some(result) = stream.next().await {
+        let (sha, content) = result?;
+        if write_loose_object(&git_dir, "blob", &content)? != sha {
+            return Err(anyhow!("Blob {} does not match its id", sha));
+        }
+        for item in &blobs[sha] {
+            write_work_file(&target.join(&item.path), &item.mode, &content)?;
+        }
+
+        fetched += 1;
+        progress.set_message(format!("Fetched {}/{} blobs", fetched, total));
+    }
+
+    progress.set_message("Writing commit...");
+    let raw_commit = reconstruct_commit(&commit).unwrap_or_else(|| {
+        out.warn(format!(
+            "Warning: Could not reproduce commit {} exactly, HEAD will have a different id",
+            commit.sha
+        ));
+        let message = if commit.message.ends_with('\n') {
+            commit.message.clone()
+        } else {
+            format!("{}\n", commit.message)
+        };
+        let author_time = unix_time(&commit.author.date).unwrap_or(0);
+        let committer_time = unix_time(&commit.committer.date).unwrap_or(0);
+        commit_object(&commit, (author_time, 0), (committer_time, 0), &message)
+    });
+    let commit_id = write_loose_object(&git_dir, "commit", &raw_commit)?;
+
+    // The parents aren't fetched, so mark the commit as a shallow boundary
+    if !commit.parents.is_empty() {
+        fs::write(git_dir.join("shallow"), format!("{}\n", commit_id)).context("Failed to write shallow file")?;
+    }
+
+    let mut config_file = format!(
+        "[core]\n\trepositoryformatversion = 0\n\tfilemode = {}\n\tbare = false\n\tlogallrefupdates = true\n\
+         [remote \"origin\"]\n\turl = https://github.com/{}/{}.git\n\tfetch = +refs/heads/*:refs/remotes/origin/*\n",
+        cfg!(unix),
+        user,
+        repo
+    );
+
+    match &branch_head {
+        Some(_) => {
+            let ref_path = git_dir.join("refs/heads").join(git_ref);
+            if let Some(parent) = ref_path.parent() {
+                fs::create_dir_all(parent).context("Failed to create ref directory")?;
+            }
+            fs::write(&ref_path, format!("{}\n", commit_id)).context("Failed to write branch ref")?;
+
+            let remote_ref = git_dir.join("refs/remotes/origin").join(git_ref);
+            if let Some(parent) = remote_ref.parent() {
+                fs::create_dir_all(parent).context("Failed to create ref directory")?;
+            }
+            fs::write(&remote_ref, format!("{}\n", commit_id)).context("Failed to write remote ref")?;
+
+            fs::write(git_dir.join("HEAD"), format!("ref: refs/heads/{}\n", git_ref)).context("Failed to write HEAD")?;
+            config_file.push_str(&format!(
+                "[branch \"{}\"]\n\tremote = origin\n\tmerge = refs/heads/{}\n",
+                git_ref, git_ref
+            ));
+        }
+        // Tags and commit ids are checked out as a detached HEAD
+        None => {
+            fs::write(git_dir.join("HEAD"), format!("{}\n", commit_id)).context("Failed to write HEAD")?;
+        }
+    }
+    fs::write(git_dir.join("config"), config_file).context("Failed to write config")?;
+
+    progress.set_message("Writing index...");
+    write_git_index(&git_dir, target, &tree.tree)?;
+
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlaceholderIndex {
+    repo: String,
+    #[serde(rename = "ref")]
+    git_ref: String,
+    files: BTreeMap<String, PlaceholderEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct PlaceholderEntry {
+    sha: String,
+    mode: String,
+    /// Size, modification time and content hash of the placeholder as written by `touch`.
+    /// Missing once the file has been hydrated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    placeholder: Option<PlaceholderStamp>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct PlaceholderStamp {
+    size: u64,
+    /// Nanoseconds since the Unix epoch
+    modified: u64,
+    /// Git blob id of the placeholder's content. Indexes written before it was
+    /// recorded leave it empty, so their sized placeholders count as real content.
+    #[serde(default)]
+    sha: String,
+}
+
+impl PlaceholderStamp {
+    fn of(path: &Path, metadata: &fs::Metadata) -> Option<Self> {
+        Some(Self {
+            size: metadata.len(),
+            modified: Self::modified(metadata)?,
+            sha: git_blob_sha(path).ok()?,
+        })
+    }
+
+    fn modified(metadata: &fs::Metadata) -> Option<u64> {
+        let modified = metadata.modified().ok()?.duration_since(std::time::UNIX_EPOCH).ok()?;
+        Some(modified.as_nanos() as u64)
+    }
+
+    /// Checks size and time first, and only hashes the file when they match. The hash
+    /// catches same-size edits on filesystems with coarse timestamps.
+    fn matches(&self, path: &Path, metadata: &fs::Metadata) -> bool {
+        self.size == metadata.len()
+            && Self::modified(metadata) == Some(self.modified)
+            && git_blob_sha(path).is_ok_and(|sha| sha == self.sha)
+    }
+}
+
+impl PlaceholderIndex {
+    /// Builds an index of the tree's files, recording the current state of every
+    /// file below `base_path` for which `is_placeholder` returns true.
+    fn new(
+        user: &str,
+        repo: &str,
+        git_ref: &str,
+        tree_items: &[TreeItem],
+        base_path: &Path,
+        is_placeholder: impl Fn(&str) -> bool,
+    ) -> Self {
+        let files = tree_items
+            .iter()
+            .filter(|item| item.item_type == "blob")
+            .map(|item| {
+                let local_path = base_path.join(&item.path);
+                let placeholder = is_placeholder(&item.path)
+                    .then(|| fs::symlink_metadata(&local_path).ok())
+                    .flatten()
+                    .and_then(|metadata| PlaceholderStamp::of(&local_path, &metadata));
+                let entry = PlaceholderEntry {
+                    sha: item.sha.clone(),
+                    mode: item.mode.clone(),
+                    placeholder,
+                };
+                (item.path.clone(), entry)
+            })
+            .collect();
+
+        Self {
+            repo: format!("{}/{}", user, repo),
+            git_ref: git_ref.to_string(),
+            files,
+        }
+    }
+}
+
+/// Writes one `<sha>\t<size>\t<path>` line per file.
+fn write_blob_manifest(path: &Path, tree_items: &[TreeItem]) -> Result<()> {
+    let manifest: String = tree_items
+        .iter()
+        .filter(|item| item.item_type == "blob")
+        .map(|item| format!("{}\t{}\t{}\n", item.sha, item.size.unwrap_or(0), item.path))
+        .collect();
+    fs::write(path, manifest).with_context(|| format!("Failed to write manifest: {}", path.display()))
+}
+
+/// Placeholders are empty files, or files the index recorded as placeholders that
+/// haven't been modified since (such as `--sized` ones). `path` is the index path
+/// of the file at `local_path`.
+fn is_placeholder(index: Option<&PlaceholderIndex>, path: &str, local_path: &Path, metadata: &fs::Metadata) -> bool {
+    if !metadata.is_file() {
+        return false;
+    }
+    metadata.len() == 0
+        || index
+            .and_then(|index| index.files.get(path))
+            .and_then(|entry| entry.placeholder.as_ref())
+            .is_some_and(|stamp| stamp.matches(local_path, metadata))
+}
+
+fn write_placeholder_index(base_path: &Path, index: &PlaceholderIndex) -> Result<()> {
+    let path = base_path.join(PLACEHOLDER_INDEX);
+    let json = serde_json::to_string_pretty(index)?;
+    fs::write(&path, json).with_context(|| format!("Failed to write placeholder index: {}", path.display()))
+}
+
+fn read_placeholder_index(base_path: &Path) -> Result<PlaceholderIndex> {
+    let path = base_path.join(PLACEHOLDER_INDEX);
+    let json = fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read placeholder index: {}", path.display()))?;
+    serde_json::from_str(&json).with_context(|| format!("Failed to parse placeholder index: {}", path.display()))
+}
+
+/// Resolves `hydrate` arguments to index paths.
+///
+/// Existing files and directories are taken relative to the current directory, anything else
+/// is a glob relative to the placeholder root. The root directory selects every file, and
+/// an empty list is an error rather than an implicit "all".
+fn select_placeholders<'a>(index: &'a PlaceholderIndex, root: &Path, targets: &[String]) -> Result<Vec<&'a str>> {
+    if targets.is_empty() {
+        return Err(anyhow!("No placeholders given, pass the placeholder root to hydrate everything"));
+    }
+
+    let mut selected = BTreeSet::new();
+
+    for target in targets {
+        let before = selected.len();
+        let path = Path::new(target);
+
+        if path.exists() {
+            let absolute = path
+                .canonicalize()
+                .with_context(|| format!("Failed to resolve path: {}", target))?;
+            let relative = absolute
+                .strip_prefix(root)
+                .map_err(|_| anyhow!("Path is outside the placeholder tree: {}", target))?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            if absolute.is_dir() {
+                let prefix = format!("{}/", relative);
+                selected.extend(
+                    index
+                        .files
+                        .keys()
+                        .filter(|p| relative.is_empty() || p.starts_with(&prefix))
+                        .map(String::as_str),
+                );
+            } else if let Some((p, _)) = index.files.get_key_value(&relative) {
+                selected.insert(p.as_str());
+            }
+        } else {
+            let glob = globset::GlobBuilder::new(target)
+                .literal_separator(true)
+                .build()
+                .with_context(|| format!("Invalid glob: {}", target))?
+                .compile_matcher();
+            selected.extend(index.files.keys().filter(|p| glob.is_match(p)).map(String::as_str));
+        }
+
+        if selected.len() == before {
+            return Err(anyhow!("No placeholders match: {}", target));
+        }
+    }
+
+    Ok(selected.into_iter().collect())
+}
+
+/// Replaces placeholders with the blobs recorded in the index and returns the paths written.
+async fn hydrate_placeholders(
+    index: &PlaceholderIndex,
+    root: &Path,
+    paths: &[&str],
+    force: bool,
+    config: &Config,
+    progress: &ProgressBar,
+    out: &Output,
+) -> Result<Vec<String>> {
+    let (user, repo) = index
+        .repo
+        .split_once('/')
+        .ok_or_else(|| anyhow!("Invalid repository in placeholder index: {}", index.repo))?;
+
+    let mut pending = Vec::new();
+    for &path in paths {
+        let entry = &index.files[path];
+        let local_path = root.join(path);
+
+        // Untouched placeholders are always replaced, anything else only when it differs
+        if let Ok(metadata) = fs::symlink_metadata(&local_path)
+            && !is_placeholder(Some(index), path, &local_path, &metadata)
+        {
+            if git_blob_sha(&local_path)? == entry.sha {
+                continue;
+            }
+            if !force {
+                out.warn(format!("Skipping modified file (use --force to overwrite): {}", path));
+                continue;
+            }
+        }
+        pending.push((path, entry, local_path));
+    }
+
+    let total = pending.len();
+    let fetches = pending.into_iter().map(|(path, entry, local_path)| async move {
+        let content = fetch_blob(user, repo, &entry.sha, config).await?;
+        Ok::<_, anyhow::Error>((path, entry, local_path, content))
+    });
+    let mut stream = futures::StreamExt::buffer_unordered(futures::stream::iter(fetches), MAX_CONCURRENT_REQUESTS);
+
+    let mut hydrated = Vec::new();
+    while let Some(result) = stream.next().await {
+        let (path, entry, local_path, content) = result?;
+        if fs::symlink_metadata(&local_path).is_ok() {
+            fs::remove_file(&local_path)
+                .with_context(|| format!("Failed to remove placeholder: {}", local_path.display()))?;
+        }
+        write_work_file(&local_path, &entry.mode, &content)?;
+
+        hydrated.push(path.to_string());
+        progress.set_message(format!("Hydrated {}/{}: {}", hydrated.len(), total, path));
+    }
+
+    Ok(hydrated)
+}
+
+async fn download_file_with_retry(
+    url: &str, 
+    file_path: &Path, 
+    config: &Config, 
+    progress: &ProgressBar,
+    max_retries: u32,
+    initial_delay: Duration,
+    out: &Output,
+) -> Result<()> {
+    let mut retries = 0;
+    let mut delay = initial_delay;
+
+    loop {
+        match download_file(url, file_path, config, progress).await {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                if retries >= max_retries {
+                    return Err(anyhow!("Failed after {} retries: {}", max_retries, e));
+                }
+                
+                retries += 1;
+                out.warn(format!(
+                    "Download failed, retrying ({}/{}): {}", 
+                    retries, max_retries, e
+                ));
+                
+                if e.to_string().contains("HTTP 403 Forbidden") {
+                    out.warn("403 Forbidden error. Check your token permissions and rate limits.");
+                }
+                
+                sleep(delay).await;
+                delay *= 2;
+            }
+        }
+    }
+}
+
+async fn download_file(
+    url: &str, 
+    file_path: &Path, 
+    config: &Config, 
+    progress: &ProgressBar,
+) -> Result<()> {
+    let response = config.client.get(url).send().await.context("Failed to download file")?;
+
+    if !response.status().is_success() {
+        return Err(anyhow!("Failed to download file: HTTP {}", response.status()));
     }
 
     let mut file = BufWriter::new(tokio::fs::File::create(file_path).await.context("Failed to create file")?);
@@ -468,14 +3479,16 @@ async fn download_file(
 
 
 async fn pull_file_or_folder(
-    user: &str,
-    repo: &str,
+    repo: (&str, &str),
     branch: &str,
     path: &str,
     output_dir: Option<&Path>,
     config: Arc<Config>,
-    progress: Arc<ProgressBar>,
+    out: &Output,
+    max_retries: u32,
 ) -> Result<()> {
+    let (user, repo) = repo;
+    let progress = Arc::new(out.progress_bar("Downloading file/folder..."));
     let url = format!(
         "{}/repos/{}/{}/contents/{}?ref={}",
         GITHUB_API_URL, user, repo, path, branch
@@ -514,8 +3527,9 @@ async fn pull_file_or_folder(
                     &file_path,
                     &config,
                     &progress,
-                    MAX_RETRIES,
+                    max_retries,
                     INITIAL_DELAY,
+                    out,
                 )
                 .await?;
             }
@@ -554,8 +3568,9 @@ async fn pull_file_or_folder(
                             &file_path,
                             &config,
                             &progress,
-                            MAX_RETRIES,
+                            max_retries,
                             INITIAL_DELAY,
+                            out,
                         )
                         .await?;
                     }
@@ -596,8 +3611,587 @@ async fn pull_file_or_folder(
         }
     }
 
-    progress.finish_with_message("Download completed");
-    Ok(())
+    progress.finish_with_message("Download completed");
+    Ok(())
+}
+
+const BROWSE_FILTER_LIMIT: usize = 1000;
+const PREVIEW_MAX_SIZE: u64 = 1024 * 1024;
+
+/// Lists one level of a tree. `tree_ish` is a ref for the root or a tree SHA below it.
+async fn fetch_tree_level(
+    user: &str,
+    repo: &str,
+    tree_ish: &str,
+    parent: &str,
+    config: &Config,
+) -> Result<Vec<TreeItem>> {
+    let url = format!("{}/repos/{}/{}/git/trees/{}", GITHUB_API_URL, user, repo, tree_ish);
+
+    let response = config
+        .client
+        .get(&url)
+        .headers(config.get_headers())
+        .send()
+        .await
+        .context("Failed to fetch tree data")?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let error_body = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+
+        return Err(anyhow!("GitHub API error {}: {}", status, error_body));
+    }
+
+    let tree_response: TreeResponse = response.json().await.context("Failed to parse tree data")?;
+    Ok(tree_response
+        .tree
+        .into_iter()
+        .map(|mut item| {
+            if !parent.is_empty() {
+                item.path = format!("{}/{}", parent, item.path);
+            }
+            item
+        })
+        .collect())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BrowseInput {
+    Normal,
+    Filter,
+    Ref,
+}
+
+struct Browser {
+    user: String,
+    repo: String,
+    git_ref: String,
+    output: Option<PathBuf>,
+    /// Loaded directory listings, keyed by directory path ("" for the root)
+    children: HashMap<String, Vec<TreeItem>>,
+    expanded: HashSet<String>,
+    marked: BTreeSet<String>,
+    /// Full recursive tree, fetched the first time the filter is used
+    all_items: Option<Vec<TreeItem>>,
+    filter: String,
+    /// Indices into `all_items` matching `filter`, best first
+    matches: Vec<usize>,
+    ref_input: String,
+    input: BrowseInput,
+    list_state: ratatui::widgets::ListState,
+    previews: HashMap<String, String>,
+    preview_scroll: u16,
+    status: String,
+    matcher: SkimMatcherV2,
+}
+
+impl Browser {
+    fn new(user: String, repo: String, git_ref: String, output: Option<PathBuf>) -> Self {
+        Self {
+            user,
+            repo,
+            git_ref,
+            output,
+            children: HashMap::new(),
+            expanded: HashSet::new(),
+            marked: BTreeSet::new(),
+            all_items: None,
+            filter: String::new(),
+            matches: Vec::new(),
+            ref_input: String::new(),
+            input: BrowseInput::Normal,
+            list_state: ratatui::widgets::ListState::default().with_selected(Some(0)),
+            previews: HashMap::new(),
+            preview_scroll: 0,
+            status: String::new(),
+            matcher: SkimMatcherV2::default().smart_case(),
+        }
+    }
+
+    /// Replaces the filter and reruns the fuzzy matcher, so `rows` doesn't on every key press.
+    fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.matches = match &self.all_items {
+            Some(all_items) if !self.filter.is_empty() => {
+                let mut matches: Vec<(i64, usize)> = all_items
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, item)| {
+                        self.matcher
+                            .fuzzy_match(&item.path, &self.filter)
+                            .map(|score| (score, i))
+                    })
+                    .collect();
+                matches.sort_by_key(|&(score, i)| (std::cmp::Reverse(score), all_items[i].path.len()));
+                matches
+                    .into_iter()
+                    .take(BROWSE_FILTER_LIMIT)
+                    .map(|(_, i)| i)
+                    .collect()
+            }
+            _ => Vec::new(),
+        };
+        self.select(0);
+    }
+
+    /// Visible rows with their depth: the expanded tree, or the fuzzy matches while filtering.
+    fn rows(&self) -> Vec<(&TreeItem, usize)> {
+        if !self.filter.is_empty()
+            && let Some(all_items) = &self.all_items
+        {
+            return self.matches.iter().map(|&i| (&all_items[i], 0)).collect();
+        }
+
+        let mut rows = Vec::new();
+        self.push_rows("", 0, &mut rows);
+        rows
+    }
+
+    fn push_rows<'a>(&'a self, dir: &str, depth: usize, rows: &mut Vec<(&'a TreeItem, usize)>) {
+        for item in self.children.get(dir).into_iter().flatten() {
+            rows.push((item, depth));
+            if item.item_type == "tree" && self.expanded.contains(&item.path) {
+                self.push_rows(&item.path, depth + 1, rows);
+            }
+        }
+    }
+
+    fn selected_index(&self) -> usize {
+        self.list_state.selected().unwrap_or(0)
+    }
+
+    fn selected(&self) -> Option<TreeItem> {
+        self.rows().get(self.selected_index()).map(|(item, _)| (*item).clone())
+    }
+
+    fn select(&mut self, index: usize) {
+        let last = self.rows().len().saturating_sub(1);
+        self.list_state.select(Some(index.min(last)));
+        self.preview_scroll = 0;
+    }
+
+    fn select_path(&mut self, path: &str) {
+        if let Some(index) = self.rows().iter().position(|(item, _)| item.path == path) {
+            self.select(index);
+        }
+    }
+
+    async fn load_dir(&mut self, dir: &str, tree_ish: &str, config: &Config) -> Result<()> {
+        if !self.children.contains_key(dir) {
+            let items = fetch_tree_level(&self.user, &self.repo, tree_ish, dir, config).await?;
+            self.children.insert(dir.to_string(), items);
+        }
+        Ok(())
+    }
+
+    async fn switch_ref(&mut self, git_ref: &str, config: &Config) -> Result<()> {
+        let root = fetch_tree_level(&self.user, &self.repo, git_ref, "", config).await?;
+
+        self.git_ref = git_ref.to_string();
+        self.children = HashMap::from([(String::new(), root)]);
+        self.expanded.clear();
+        self.marked.clear();
+        self.all_items = None;
+        self.set_filter(String::new());
+        Ok(())
+    }
+
+    /// Expands a directory, or loads the preview of a file.
+    async fn open(&mut self, config: &Config) -> Result<()> {
+        let Some(item) = self.selected() else {
+            return Ok(());
+        };
+
+        match item.item_type.as_str() {
+            "tree" if !self.filter.is_empty() => self.reveal(&item.path, config).await?,
+            "tree" => {
+                self.load_dir(&item.path, &item.sha, config).await?;
+                self.expanded.insert(item.path);
+            }
+            "blob" if !self.previews.contains_key(&item.sha) => {
+                let preview = if item.size.is_some_and(|size| size > PREVIEW_MAX_SIZE) {
+                    "(file too large to preview)".to_string()
+                } else {
+                    let content = fetch_blob(&self.user, &self.repo, &item.sha, config).await?;
+                    if content.contains(&0) {
+                        format!("(binary file, {})", format_size(content.len() as u64, BINARY))
+                    } else {
+                        String::from_utf8_lossy(&content).replace('\t', "    ")
+                    }
+                };
+                self.previews.insert(item.sha, preview);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// Leaves the filter and expands every ancestor of `path` so it shows in the tree.
+    async fn reveal(&mut self, path: &str, config: &Config) -> Result<()> {
+        let shas: HashMap<String, String> = self
+            .all_items
+            .iter()
+            .flatten()
+            .filter(|item| item.item_type == "tree")
+            .map(|item| (item.path.clone(), item.sha.clone()))
+            .collect();
+
+        let parts: Vec<&str> = path.split('/').collect();
+        for end in 1..=parts.len() {
+            let dir = parts[..end].join("/");
+            if let Some(sha) = shas.get(&dir) {
+                self.load_dir(&dir, sha, config).await?;
+                self.expanded.insert(dir);
+            }
+        }
+
+        self.set_filter(String::new());
+        self.select_path(path);
+        Ok(())
+    }
+
+    /// Collapses the selected directory, or jumps to the parent of the selection.
+    fn close(&mut self) {
+        let Some(item) = self.selected() else {
+            return;
+        };
+
+        if self.filter.is_empty() && self.expanded.remove(&item.path) {
+            return;
+        }
+        if let Some((parent, _)) = item.path.rsplit_once('/') {
+            let parent = parent.to_string();
+            self.select_path(&parent);
+        }
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(item) = self.selected()
+            && !self.marked.remove(&item.path)
+        {
+            self.marked.insert(item.path);
+        }
+        let next = self.selected_index() + 1;
+        self.select(next);
+    }
+
+    /// Pulls every marked path and returns how many succeeded. A path is unmarked once
+    /// it is pulled, so failed paths, and the rest if the pull is cancelled, stay marked.
+    async fn pull_marked(&mut self, config: &Arc<Config>) -> (usize, Vec<(String, anyhow::Error)>) {
+        let mut pulled = 0;
+        let mut failed = Vec::new();
+        for path in self.marked.clone() {
+            let result = pull_file_or_folder(
+                (&self.user, &self.repo),
+                &self.git_ref,
+                &path,
+                self.output.as_deref(),
+                Arc::clone(config),
+                // Warnings would draw over the browser
+                &Output::buffered(),
+                BROWSE_MAX_RETRIES,
+            )
+            .await;
+            match result {
+                Ok(()) => {
+                    self.marked.remove(&path);
+                    pulled += 1;
+                }
+                Err(e) => failed.push((path, e)),
+            }
+        }
+        (pulled, failed)
+    }
+}
+
+fn draw_browser(frame: &mut ratatui::Frame, browser: &mut Browser) {
+    use ratatui::layout::{Constraint, Layout};
+    use ratatui::style::{Color as TuiColor, Modifier, Style};
+    use ratatui::text::{Line, Span};
+    use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph};
+
+    let [header, body, footer] =
+        Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+    let [left, right] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(body);
+
+    let title = format!(
+        " {}/{} @ {}   {} marked",
+        browser.user,
+        browser.repo,
+        browser.git_ref,
+        browser.marked.len()
+    );
+    frame.render_widget(
+        Paragraph::new(title).style(Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED)),
+        header,
+    );
+
+    let filtering = !browser.filter.is_empty();
+    let items: Vec<ListItem> = browser
+        .rows()
+        .into_iter()
+        .map(|(item, depth)| {
+            let name = if filtering {
+                item.path.clone()
+            } else {
+                item.path.rsplit('/').next().unwrap_or(&item.path).to_string()
+            };
+            let marker = match item.item_type.as_str() {
+                "tree" if browser.expanded.contains(&item.path) => "▾ ",
+                "tree" => "▸ ",
+                _ => "  ",
+            };
+            let style = match (item.item_type.as_str(), item.mode.as_str()) {
+                ("tree", _) => Style::new().fg(TuiColor::Blue).add_modifier(Modifier::BOLD),
+                ("commit", _) => Style::new().fg(TuiColor::Yellow),
+                (_, "120000") => Style::new().fg(TuiColor::Cyan),
+                (_, "100755") => Style::new().fg(TuiColor::Green),
+                _ => Style::new(),
+            };
+            let mark = if browser.marked.contains(&item.path) {
+                Span::styled("● ", Style::new().fg(TuiColor::Magenta))
+            } else {
+                Span::raw("  ")
+            };
+
+            ListItem::new(Line::from(vec![
+                mark,
+                Span::raw("  ".repeat(depth)),
+                Span::raw(marker),
+                Span::styled(name, style),
+            ]))
+        })
+        .collect();
+
+    let list_title = if filtering {
+        format!(" Matches for '{}' ", browser.filter)
+    } else {
+        " Tree ".to_string()
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(list_title))
+        .highlight_style(Style::new().add_modifier(Modifier::REVERSED));
+    frame.render_stateful_widget(list, left, &mut browser.list_state);
+
+    let selected = browser.selected();
+    let preview = match &selected {
+        Some(item) => match browser.previews.get(&item.sha) {
+            Some(preview) => preview.clone(),
+            None if item.item_type == "tree" => match browser.children.get(&item.path) {
+                Some(children) => format!("{}/\n\n{} entries", item.path, children.len()),
+                None => format!("{}/\n\nPress Enter to expand", item.path),
+            },
+            None if item.item_type == "commit" => format!("{}\n\nSubmodule at commit {}", item.path, item.sha),
+            None => format!(
+                "{}\n\nSize: {}\nSHA: {}\n\nPress Enter to preview",
+                item.path,
+                item.size.map(|s| format_size(s, BINARY)).unwrap_or_default(),
+                item.sha
+            ),
+        },
+        None => String::new(),
+    };
+    let preview_title = selected.map(|item| format!(" {} ", item.path)).unwrap_or_default();
+    frame.render_widget(
+        Paragraph::new(preview)
+            .block(Block::default().borders(Borders::ALL).title(preview_title))
+            .scroll((browser.preview_scroll, 0)),
+        right,
+    );
+
+    let footer_text = match browser.input {
+        BrowseInput::Filter => format!("/{}█", browser.filter),
+        BrowseInput::Ref => format!("Switch to ref: {}█", browser.ref_input),
+        BrowseInput::Normal if !browser.status.is_empty() => browser.status.clone(),
+        BrowseInput::Normal => {
+            "↑↓ move  →/Enter open  ← close  / filter  space mark  p pull  b ref  J/K scroll preview  q quit"
+                .to_string()
+        }
+    };
+    frame.render_widget(Paragraph::new(footer_text), footer);
+}
+
+fn show_browser_status(
+    terminal: &mut ratatui::DefaultTerminal,
+    browser: &mut Browser,
+    status: &str,
+) -> Result<()> {
+    browser.status = status.to_string();
+    terminal.draw(|frame| draw_browser(frame, browser))?;
+    Ok(())
+}
+
+async fn run_browser(terminal: &mut ratatui::DefaultTerminal, browser: &mut Browser, config: &Arc<Config>) -> Result<()> {
+    use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+
+    loop {
+        terminal.draw(|frame| draw_browser(frame, browser))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            return Ok(());
+        }
+
+        match browser.input {
+            BrowseInput::Filter => match key.code {
+                KeyCode::Esc => {
+                    browser.set_filter(String::new());
+                    browser.input = BrowseInput::Normal;
+                }
+                KeyCode::Enter => browser.input = BrowseInput::Normal,
+                KeyCode::Backspace => {
+                    let mut filter = browser.filter.clone();
+                    filter.pop();
+                    browser.set_filter(filter);
+                }
+                KeyCode::Char(c) => {
+                    let filter = format!("{}{}", browser.filter, c);
+                    browser.set_filter(filter);
+                }
+                _ => {}
+            },
+            BrowseInput::Ref => match key.code {
+                KeyCode::Esc => browser.input = BrowseInput::Normal,
+                KeyCode::Enter => {
+                    browser.input = BrowseInput::Normal;
+                    let git_ref = std::mem::take(&mut browser.ref_input);
+                    if !git_ref.is_empty() {
+                        show_browser_status(terminal, browser, &format!("Switching to {}...", git_ref))?;
+                        browser.status = match browser.switch_ref(&git_ref, config).await {
+                            Ok(()) => format!("Switched to {}", git_ref),
+                            Err(e) => format!("Error: {}", e),
+                        };
+                    }
+                }
+                KeyCode::Backspace => {
+                    browser.ref_input.pop();
+                }
+                KeyCode::Char(c) => browser.ref_input.push(c),
+                _ => {}
+            },
+            BrowseInput::Normal => {
+                browser.status.clear();
+                let index = browser.selected_index();
+
+                match key.code {
+                    KeyCode::Char('q') => return Ok(()),
+                    KeyCode::Esc if browser.filter.is_empty() => return Ok(()),
+                    KeyCode::Esc => browser.set_filter(String::new()),
+                    KeyCode::Up | KeyCode::Char('k') => browser.select(index.saturating_sub(1)),
+                    KeyCode::Down | KeyCode::Char('j') => browser.select(index + 1),
+                    KeyCode::PageUp => browser.select(index.saturating_sub(20)),
+                    KeyCode::PageDown => browser.select(index + 20),
+                    KeyCode::Home | KeyCode::Char('g') => browser.select(0),
+                    KeyCode::End | KeyCode::Char('G') => browser.select(usize::MAX),
+                    KeyCode::Char('J') => browser.preview_scroll = browser.preview_scroll.saturating_add(1),
+                    KeyCode::Char('K') => browser.preview_scroll = browser.preview_scroll.saturating_sub(1),
+                    KeyCode::Right | KeyCode::Enter | KeyCode::Char('l') => {
+                        show_browser_status(terminal, browser, "Loading...")?;
+                        browser.status.clear();
+                        if let Err(e) = browser.open(config).await {
+                            browser.status = format!("Error: {}", e);
+                        }
+                    }
+                    KeyCode::Left | KeyCode::Char('h') => browser.close(),
+                    KeyCode::Char(' ') => browser.toggle_mark(),
+                    KeyCode::Char('/') => {
+                        if browser.all_items.is_none() {
+                            show_browser_status(terminal, browser, "Loading full tree...")?;
+                            // Warnings would draw over the browser
+                            let out = Output::buffered();
+                            match fetch_tree_recursive(&browser.user, &browser.repo, &browser.git_ref, config, &out)
+                                .await
+                            {
+                                Ok(items) => browser.all_items = Some(items),
+                                Err(e) => {
+                                    browser.status = format!("Error: {}", e);
+                                    continue;
+                                }
+                            }
+                            browser.status.clear();
+                        }
+                        browser.input = BrowseInput::Filter;
+                    }
+                    KeyCode::Char('b') => browser.input = BrowseInput::Ref,
+                    KeyCode::Char('p') if browser.marked.is_empty() => {
+                        browser.status = "Nothing marked, mark files with space".to_string();
+                    }
+                    KeyCode::Char('p') => {
+                        show_browser_status(terminal, browser, "Pulling marked files... (Esc to cancel)")?;
+                        let destination = browser
+                            .output
+                            .as_ref()
+                            .map(|p| p.display().to_string())
+                            .unwrap_or_else(|| ".".to_string());
+
+                        // Keep reading keys while pulling so Esc can cancel a stuck download
+                        let result = {
+                            let pull = browser.pull_marked(config);
+                            tokio::pin!(pull);
+                            'pull: loop {
+                                tokio::select! {
+                                    result = &mut pull => break 'pull Some(result),
+                                    _ = sleep(Duration::from_millis(100)) => {
+                                        while event::poll(Duration::ZERO)? {
+                                            if let Event::Key(key) = event::read()?
+                                                && key.kind == KeyEventKind::Press
+                                                && key.code == KeyCode::Esc
+                                            {
+                                                break 'pull None;
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        };
+                        let Some((pulled, failed)) = result else {
+                            browser.status = format!("Pull cancelled, {} paths still marked", browser.marked.len());
+                            continue;
+                        };
+                        browser.status = match failed.first() {
+                            None => format!("Pulled {} paths into {}", pulled, destination),
+                            Some((path, e)) => format!(
+                                "Pulled {} paths into {}, {} failed and stay marked ({}: {})",
+                                pulled,
+                                destination,
+                                failed.len(),
+                                path,
+                                e
+                            ),
+                        };
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+async fn browse_repo(
+    user: String,
+    repo: String,
+    git_ref: String,
+    output: Option<PathBuf>,
+    config: &Arc<Config>,
+) -> Result<()> {
+    let mut browser = Browser::new(user, repo, git_ref, output);
+    let root = fetch_tree_level(&browser.user, &browser.repo, &browser.git_ref, "", config).await?;
+    browser.children.insert(String::new(), root);
+
+    let mut terminal = ratatui::init();
+    let result = run_browser(&mut terminal, &mut browser, config).await;
+    ratatui::restore();
+    result
 }
 
 async fn download_repo_zip(
@@ -607,6 +4201,7 @@ async fn download_repo_zip(
     output_dir: Option<&Path>,
     config: &Config,
     progress: &ProgressBar,
+    out: &Output,
 ) -> Result<()> {
     let url = format!(
         "{}/repos/{}/{}/zipball/{}",
@@ -614,6 +4209,8 @@ async fn download_repo_zip(
     );
 
     let output_file = if let Some(dir) = output_dir {
+        fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {}", dir.display()))?;
         dir.join(format!("{}-{}.zip", repo, branch))
     } else {
         PathBuf::from(format!("{}-{}.zip", repo, branch))
@@ -643,7 +4240,7 @@ async fn download_repo_zip(
                 }
 
                 retries += 1;
-                progress.println(format!(
+                out.warn(format!(
                     "Download failed, retrying ({}/{}): {}",
                     retries, max_retries, e
                 ));
@@ -703,62 +4300,874 @@ async fn download_repo_zip_internal(
     Ok(())
 }
 
+/// Inclusive, 1-based line range parsed from `A:B`, `A:` or `:B`.
+#[derive(Debug, Clone, Copy)]
+struct LineRange {
+    start: usize,
+    end: Option<usize>,
+}
+
+impl LineRange {
+    fn contains(&self, line: usize) -> bool {
+        line >= self.start && self.end.is_none_or(|end| line <= end)
+    }
+
+    fn is_past(&self, line: usize) -> bool {
+        self.end.is_some_and(|end| line > end)
+    }
+}
+
+fn parse_line_range(s: &str) -> std::result::Result<LineRange, String> {
+    let (start, end) = s.split_once(':').unwrap_or((s, s));
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<usize>()
+            .map_err(|_| format!("invalid line number: {}", v))
+    };
+
+    let start = if start.trim().is_empty() { 1 } else { parse(start)? };
+    let end = if end.trim().is_empty() { None } else { Some(parse(end)?) };
+
+    if start == 0 {
+        return Err("line numbers start at 1".to_string());
+    }
+    if end.is_some_and(|end| end < start) {
+        return Err(format!("empty line range: {}", s));
+    }
+
+    Ok(LineRange { start, end })
+}
+
+/// Splits `path@ref` into its parts. The ref is optional, and an `@` followed by more
+/// path components (`node_modules/@types/node`) is left in the path.
+fn split_path_ref(spec: &str) -> (&str, Option<&str>) {
+    match spec.rsplit_once('@') {
+        Some((path, git_ref))
+            if !path.is_empty() && !path.ends_with('/') && !git_ref.is_empty() && !git_ref.contains('/') =>
+        {
+            (path, Some(git_ref))
+        }
+        _ => (spec, None),
+    }
+}
+
+fn highlight_source(path: &str, text: &str, lines: Option<LineRange>) -> Result<String> {
+    use syntect::easy::HighlightLines;
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+    use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let theme_set = ThemeSet::load_defaults();
+
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let extension = name.rsplit_once('.').map(|(_, ext)| ext).unwrap_or(name);
+    let syntax = syntax_set
+        .find_syntax_by_extension(extension)
+        .or_else(|| syntax_set.find_syntax_by_first_line(text.lines().next().unwrap_or("")))
+        .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+    let mut highlighter = HighlightLines::new(syntax, &theme_set.themes["base16-ocean.dark"]);
+    let mut out = String::new();
+
+    for (i, line) in LinesWithEndings::from(text).enumerate() {
+        let line_number = i + 1;
+        if lines.is_some_and(|r| r.is_past(line_number)) {
+            break;
+        }
+        // Earlier lines still have to go through the highlighter to keep its state
+        let ranges = highlighter
+            .highlight_line(line, &syntax_set)
+            .context("Failed to highlight file")?;
+        if lines.is_none_or(|r| r.contains(line_number)) {
+            out.push_str(&as_24_bit_terminal_escaped(&ranges, false));
+        }
+    }
+    out.push_str("\x1b[0m");
+
+    Ok(out)
+}
+
+async fn cat_file(
+    out: &Output,
+    repo: (&str, &str),
+    path: &str,
+    git_ref: Option<&str>,
+    lines: Option<LineRange>,
+    highlight: bool,
+    config: &Config,
+) -> Result<()> {
+    let (user, repo) = repo;
+    let url = format!("{}/repos/{}/{}/contents/{}", GITHUB_API_URL, user, repo, path);
+
+    let mut request = config.client.get(&url);
+    if let Some(git_ref) = git_ref {
+        request = request.query(&[("ref", git_ref)]);
+    }
+
+    let response = request
+        .headers(config.get_headers())
+        .header(reqwest::header::ACCEPT, "application/vnd.github.raw")
+        .send()
+        .await
+        .context("Failed to fetch file")?;
+
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Err(anyhow!("File not found: {}", path));
+    } else if !status.is_success() {
+        return Err(anyhow!(
+            "GitHub API error {}: {}",
+            status,
+            response.text().await.unwrap_or_else(|_| "Unknown error".to_string())
+        ));
+    }
+
+    // Directories come back as a JSON listing even when raw content is requested
+    let is_listing = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if is_listing {
+        return Err(anyhow!("Path is not a file: {}", path));
+    }
+
+    // A batch job's output is collected and printed together with the others
+    if out.is_buffered() {
+        let mut buffer = Vec::new();
+        write_file_content(response, path, lines, highlight, &mut buffer).await?;
+        write!(out, "{}", String::from_utf8_lossy(&buffer));
+        return Ok(());
+    }
+
+    let mut stdout = BufWriter::new(tokio::io::stdout());
+    let result = write_file_content(response, path, lines, highlight, &mut stdout).await;
+
+    let result = match result {
+        Ok(()) => stdout.flush().await.context("Failed to write to stdout"),
+        err => err,
+    };
+
+    match result {
+        // Stop quietly when piped into something like `head`
+        Err(e) if e
+            .downcast_ref::<std::io::Error>()
+            .is_some_and(|e| e.kind() == std::io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        other => other,
+    }
+}
+
+async fn write_file_content(
+    response: reqwest::Response,
+    path: &str,
+    lines: Option<LineRange>,
+    highlight: bool,
+    out: &mut (impl tokio::io::AsyncWrite + Unpin),
+) -> Result<()> {
+    if highlight {
+        let bytes = response.bytes().await.context("Failed to read file")?;
+        let text = String::from_utf8_lossy(&bytes);
+        let highlighted = highlight_source(path, &text, lines)?;
+        out.write_all(highlighted.as_bytes())
+            .await
+            .context("Failed to write to stdout")
+    } else {
+        write_lines(response, lines, out).await
+    }
+}
+
+async fn write_lines(
+    response: reqwest::Response,
+    lines: Option<LineRange>,
+    out: &mut (impl tokio::io::AsyncWrite + Unpin),
+) -> Result<()> {
+    let mut stream = response.bytes_stream();
+    let mut line_number = 1;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.context("Failed to read chunk")?;
+
+        let Some(range) = lines else {
+            out.write_all(&chunk).await.context("Failed to write to stdout")?;
+            continue;
+        };
+
+        for piece in chunk.split_inclusive(|&b| b == b'\n') {
+            if range.is_past(line_number) {
+                return Ok(());
+            }
+            if range.contains(line_number) {
+                out.write_all(piece).await.context("Failed to write to stdout")?;
+            }
+            if piece.ends_with(b"\n") {
+                line_number += 1;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchMode {
+    Substring,
+    Exact,
+    Regex,
+    Glob,
+    Fuzzy,
+}
+
+enum Pattern {
+    Text(String),
+    Regex(regex::Regex),
+    Glob(globset::GlobMatcher),
+    Fuzzy(Box<SkimMatcherV2>),
+}
+
+struct NameMatcher {
+    query: String,
+    pattern: Pattern,
+    exact: bool,
+    ignore_case: bool,
+    full_path: bool,
+    filter: EntryFilter,
+}
+
+impl NameMatcher {
+    fn new(query: &str, mode: MatchMode, ignore_case: bool, full_path: bool) -> Result<Self> {
+        let pattern = match mode {
+            MatchMode::Substring | MatchMode::Exact => Pattern::Text(if ignore_case {
+                query.to_lowercase()
+            } else {
+                query.to_string()
+            }),
+            MatchMode::Regex => Pattern::Regex(
+                regex::RegexBuilder::new(query)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .with_context(|| format!("Invalid regex: {}", query))?,
+            ),
+            MatchMode::Glob => Pattern::Glob(
+                globset::GlobBuilder::new(query)
+                    .case_insensitive(ignore_case)
+                    .literal_separator(true)
+                    .build()
+                    .with_context(|| format!("Invalid glob: {}", query))?
+                    .compile_matcher(),
+            ),
+            MatchMode::Fuzzy => {
+                let matcher = SkimMatcherV2::default();
+                Pattern::Fuzzy(Box::new(if ignore_case {
+                    matcher.ignore_case()
+                } else {
+                    matcher.smart_case()
+                }))
+            }
+        };
+
+        // Globs containing a separator only make sense against the whole path
+        let full_path = full_path || (mode == MatchMode::Glob && query.contains('/'));
+
+        Ok(Self {
+            query: query.to_string(),
+            pattern,
+            exact: mode == MatchMode::Exact,
+            ignore_case,
+            full_path,
+            filter: EntryFilter::default(),
+        })
+    }
+
+    fn with_filter(mut self, filter: EntryFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Scores a tree entry, taking both its name and metadata into account.
+    fn matches(&self, item: &TreeItem) -> Option<i64> {
+        if !self.filter.matches(item) {
+            return None;
+        }
+        self.score(&item.path)
+    }
+
+    /// Returns a score for `path` if it matches, higher is better.
+    fn score(&self, path: &str) -> Option<i64> {
+        let target = if self.full_path {
+            path
+        } else {
+            path.rsplit('/').next().unwrap_or(path)
+        };
+
+        match &self.pattern {
+            Pattern::Text(text) => {
+                let target = if self.ignore_case {
+                    target.to_lowercase()
+                } else {
+                    target.to_string()
+                };
+                if text.is_empty() {
+                    Some(0)
+                } else if self.exact {
+                    (target == *text).then_some(0)
+                } else {
+                    // Prefer matches that cover more of the name
+                    target
+                        .contains(text.as_str())
+                        .then(|| text.len() as i64 - target.len() as i64)
+                }
+            }
+            Pattern::Regex(re) => re.is_match(target).then_some(0),
+            Pattern::Glob(glob) => glob.is_match(target).then_some(0),
+            Pattern::Fuzzy(matcher) => matcher.fuzzy_match(target, &self.query),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum EntryType {
+    File,
+    Dir,
+    Symlink,
+    Submodule,
+    Exec,
+}
+
+impl EntryType {
+    fn matches(self, item: &TreeItem) -> bool {
+        match self {
+            EntryType::File => item.item_type == "blob" && item.mode != "120000",
+            EntryType::Dir => item.item_type == "tree",
+            EntryType::Symlink => item.mode == "120000",
+            EntryType::Submodule => item.item_type == "commit",
+            EntryType::Exec => item.mode == "100755",
+        }
+    }
+}
+
+#[derive(Default)]
+struct EntryFilter {
+    extension: Option<String>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    entry_type: Option<EntryType>,
+}
+
+impl EntryFilter {
+    fn has_size_bounds(&self) -> bool {
+        self.min_size.is_some() || self.max_size.is_some()
+    }
+
+    fn matches(&self, item: &TreeItem) -> bool {
+        if let Some(entry_type) = self.entry_type
+            && !entry_type.matches(item)
+        {
+            return false;
+        }
+
+        if let Some(ext) = &self.extension {
+            let name = item.path.rsplit('/').next().unwrap_or(&item.path);
+            match name.rsplit_once('.') {
+                Some((stem, found)) if !stem.is_empty() && found.eq_ignore_ascii_case(ext) => {}
+                _ => return false,
+            }
+        }
+
+        if self.has_size_bounds() {
+            // Only blobs carry a size
+            let Some(size) = item.size else {
+                return false;
+            };
+            if self.min_size.is_some_and(|min| size < min)
+                || self.max_size.is_some_and(|max| size > max)
+            {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+/// Parses sizes like `512`, `10K`, `1.5MB` or `2GiB` into bytes.
+fn parse_size(s: &str) -> std::result::Result<u64, String> {
+    let s = s.trim();
+    let split = s
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid size: {}", s))?;
+    let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        other => return Err(format!("unknown size unit: {}", other)),
+    };
+
+    Ok((number * multiplier as f64) as u64)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Paths,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum LinkMode {
+    /// Hyperlink when writing to a terminal known to support it
+    Auto,
+    /// Print the URL after each name
+    Print,
+    /// Wrap each name in an OSC-8 terminal hyperlink
+    Hyperlink,
+    /// Don't link names
+    Never,
+}
+
+impl LinkMode {
+    /// Settles `auto` against the terminal. `None` means no links.
+    fn resolve(self) -> Option<LinkMode> {
+        match self {
+            LinkMode::Auto => {
+                (std::io::stdout().is_terminal() && terminal_supports_hyperlinks()).then_some(LinkMode::Hyperlink)
+            }
+            LinkMode::Never => None,
+            mode => Some(mode),
+        }
+    }
+}
+
+/// Percent-encodes everything but unreserved characters and `/`.
+fn encode_url_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+fn github_url(user: &str, repo: &str, git_ref: &str, path: &str, is_dir: bool) -> String {
+    format!(
+        "https://github.com/{}/{}/{}/{}/{}",
+        user,
+        repo,
+        if is_dir { "tree" } else { "blob" },
+        encode_url_path(git_ref),
+        encode_url_path(path)
+    )
+}
+
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+fn format_link(text: &str, url: &str, links: Option<LinkMode>) -> String {
+    match links {
+        Some(LinkMode::Print) => format!("{} {}", text, url),
+        Some(LinkMode::Hyperlink) => hyperlink(url, text),
+        Some(LinkMode::Auto | LinkMode::Never) | None => text.to_string(),
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FindMatch {
+    path: String,
+    #[serde(rename = "type")]
+    item_type: String,
+    branches: Vec<String>,
+    size: Option<u64>,
+    score: i64,
+    url: String,
+}
+
 async fn find_file_in_repo(
     user: &str,
     repo: &str,
-    filename: &str,
+    matcher: &NameMatcher,
     branch: Option<&str>,
+    limit: Option<usize>,
     config: &Config,
-    progress: &ProgressBar,
-    exact_match: bool,
-) -> Result<()> {
-    let branches = if let Some(branch) = branch {
-        vec![branch.to_string()]
+    out: &Output,
+) -> Result<Vec<FindMatch>> {
+    let progress = out.progress_bar("Searching for file...");
+    let branches: Vec<Branch> = if let Some(branch) = branch {
+        vec![Branch {
+            name: branch.to_string(),
+            commit: BranchCommit {
+                sha: branch.to_string(),
+            },
+            protected: false,
+        }]
     } else {
         // Fetch all branches if no specific branch is provided
+        progress.set_message("Fetching branches...");
         let url = format!("{}/repos/{}/{}/branches", GITHUB_API_URL, user, repo);
+        fetch_all_pages(&url, config)
+            .await
+            .context("Failed to fetch branches")?
+    };
+    let branch_count = branches.len();
+
+    // Branches pointing at the same commit share a tree, so fetch it once
+    let mut by_commit: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for branch in branches {
+        by_commit.entry(branch.commit.sha).or_default().push(branch.name);
+    }
+
+    progress.set_message(format!(
+        "Fetching {} tree(s) for {} branch(es)...",
+        by_commit.len(),
+        branch_count
+    ));
+
+    let fetches = by_commit.into_iter().map(|(sha, names)| async move {
+        fetch_tree(user, repo, &sha, config, out)
+            .await
+            .with_context(|| format!("Failed to fetch tree for branch: {}", names.join(", ")))
+            .map(|tree| (names, tree))
+    });
+    let trees: Vec<(Vec<String>, TreeResponse)> =
+        futures::StreamExt::buffer_unordered(futures::stream::iter(fetches), MAX_CONCURRENT_REQUESTS)
+            .try_collect()
+            .await?;
+
+    // Different commits can still have identical root trees
+    let mut by_tree: BTreeMap<String, (Vec<String>, Vec<TreeItem>)> = BTreeMap::new();
+    for (names, TreeResponse { sha, tree, .. }) in trees {
+        by_tree
+            .entry(sha)
+            .or_insert_with(|| (Vec::new(), tree))
+            .0
+            .extend(names);
+    }
+
+    progress.set_message(format!("Searching {} unique tree(s)...", by_tree.len()));
+
+    let mut matches: BTreeMap<String, FindMatch> = BTreeMap::new();
+    for (names, tree_items) in by_tree.into_values() {
+        for item in tree_items {
+            if let Some(score) = matcher.matches(&item) {
+                let url = github_url(user, repo, &names[0], &item.path, item.item_type == "tree");
+                matches
+                    .entry(item.path.clone())
+                    .or_insert_with(|| FindMatch {
+                        path: item.path,
+                        item_type: item.item_type,
+                        branches: Vec::new(),
+                        size: item.size,
+                        score,
+                        url,
+                    })
+                    .branches
+                    .extend(names.iter().cloned());
+            }
+        }
+    }
+
+    progress.finish_and_clear();
+
+    let mut matches: Vec<FindMatch> = matches.into_values().collect();
+    matches.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.path.cmp(&b.path)));
+    if let Some(limit) = limit {
+        matches.truncate(limit);
+    }
+    for m in &mut matches {
+        m.branches.sort();
+    }
+
+    Ok(matches)
+}
+
+fn print_find_matches(
+    out: &Output,
+    matches: &[FindMatch],
+    show_sizes: bool,
+    format: OutputFormat,
+    links: Option<LinkMode>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            writeln!(out, "{}", serde_json::to_string_pretty(matches)?);
+        }
+        OutputFormat::Paths => {
+            for m in matches {
+                writeln!(out, "{}", format_link(&m.path, &m.url, links));
+            }
+        }
+        OutputFormat::Text => {
+            for m in matches {
+                let path = format_link(&m.path, &m.url, links);
+                let path = match m.size {
+                    Some(size) if show_sizes => {
+                        format!("{} ({})", path, format_size(size, BINARY))
+                    }
+                    _ => path,
+                };
+                if m.branches.len() == 1 {
+                    writeln!(out, "Found: {} in branch: {}", path, m.branches[0]);
+                } else {
+                    writeln!(out, "Found: {} in branches: {}", path, m.branches.join(", "));
+                }
+            }
+
+            writeln!(out, "Search completed: {} match(es)", matches.len());
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct CodeSearchResponse {
+    total_count: u64,
+    incomplete_results: bool,
+    items: Vec<CodeSearchItem>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CodeSearchItem {
+    path: String,
+    html_url: String,
+    #[serde(default)]
+    text_matches: Vec<TextMatch>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct TextMatch {
+    fragment: String,
+    #[serde(default)]
+    matches: Vec<TextMatchSpan>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct TextMatchSpan {
+    indices: [usize; 2],
+}
+
+/// How long GitHub asks us to wait before the next request, if it said so.
+fn rate_limit_delay(response: &reqwest::Response) -> Option<Duration> {
+    let headers = response.headers();
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
+    };
+
+    if let Some(secs) = header("retry-after") {
+        return Some(Duration::from_secs(secs));
+    }
+
+    if header("x-ratelimit-remaining") == Some(0) {
+        let reset = header("x-ratelimit-reset")?;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_secs();
+        return Some(Duration::from_secs(reset.saturating_sub(now) + 1));
+    }
+
+    None
+}
+
+fn highlight_fragment(fragment: &str, spans: &[TextMatchSpan]) -> String {
+    let chars: Vec<char> = fragment.chars().collect();
+    let mut out = String::new();
+    let mut pos = 0;
+
+    for span in spans {
+        let [start, end] = span.indices;
+        if start < pos || end > chars.len() || start >= end {
+            continue;
+        }
+        out.extend(&chars[pos..start]);
+        let matched: String = chars[start..end].iter().collect();
+        out.push_str(&matched.yellow().bold().to_string());
+        pos = end;
+    }
+    out.extend(&chars[pos..]);
+    out
+}
+
+fn build_code_query(
+    user: &str,
+    repo: &str,
+    query: &str,
+    path: Option<&str>,
+    extension: Option<&str>,
+) -> String {
+    let mut q = format!("{} repo:{}/{}", query, user, repo);
+    if let Some(path) = path {
+        q.push_str(&format!(" path:{}", path));
+    }
+    if let Some(ext) = extension {
+        q.push_str(&format!(" extension:{}", ext.trim_start_matches('.')));
+    }
+    q
+}
+
+async fn search_code_in_repo(
+    q: &str,
+    limit: Option<usize>,
+    config: &Config,
+    progress: &ProgressBar,
+    out: &Output,
+) -> Result<(Vec<CodeSearchItem>, u64)> {
+    // The search API never returns more than 1000 results
+    let limit = limit.unwrap_or(1000).min(1000);
+    let per_page = limit.clamp(1, 100);
+
+    let url = format!("{}/search/code", GITHUB_API_URL);
+    let mut items = Vec::new();
+    let mut total_count = 0;
+    let mut page = 1;
+    let mut retries = 0;
+
+    while items.len() < limit {
+        progress.set_message(format!("Searching code (page {})...", page));
+
         let response = config
             .client
             .get(&url)
             .headers(config.get_headers())
+            .header(
+                reqwest::header::ACCEPT,
+                "application/vnd.github.text-match+json",
+            )
+            .query(&[
+                ("q", q),
+                ("per_page", &per_page.to_string()),
+                ("page", &page.to_string()),
+            ])
             .send()
             .await
-            .context("Failed to fetch branches")?;
-
-        if !response.status().is_success() {
+            .context("Failed to search code")?;
+
+        let status = response.status();
+
+        if status == reqwest::StatusCode::FORBIDDEN
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        {
+            // Search has its own per-minute limit, so short waits are worth it
+            match rate_limit_delay(&response) {
+                Some(delay) if retries < MAX_SEARCH_RETRIES && delay <= MAX_SEARCH_WAIT => {
+                    retries += 1;
+                    out.warn(format!(
+                        "Search rate limit hit, waiting {}s ({}/{})",
+                        delay.as_secs(),
+                        retries,
+                        MAX_SEARCH_RETRIES
+                    ));
+                    sleep(delay).await;
+                    continue;
+                }
+                Some(delay) => {
+                    return Err(anyhow!(
+                        "Code search rate limit exceeded. Try again in {}s",
+                        delay.as_secs()
+                    ));
+                }
+                None => {
+                    return Err(anyhow!(
+                        "Code search rate limit exceeded. Try using a GitHub token with --pat"
+                    ));
+                }
+            }
+        } else if status == reqwest::StatusCode::UNAUTHORIZED {
             return Err(anyhow!(
-                "Failed to fetch branches: {}",
-                response.text().await.unwrap_or_else(|_| "Unknown error".to_string())
+                "Code search requires authentication. Provide a token with --pat"
             ));
+        } else if !status.is_success() {
+            let error_body = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+
+            let error_msg = serde_json::from_str::<serde_json::Value>(&error_body)
+                .ok()
+                .and_then(|j| j.get("message").and_then(|m| m.as_str().map(String::from)))
+                .unwrap_or(error_body);
+
+            return Err(anyhow!("GitHub API error {}: {}", status, error_msg));
         }
 
-        let branches: Vec<serde_json::Value> = response
+        let result: CodeSearchResponse = response
             .json()
             .await
-            .context("Failed to parse branches")?;
+            .context("Failed to parse search results")?;
 
-        branches
-            .into_iter()
-            .filter_map(|b| b.get("name").and_then(|n| n.as_str().map(|s| s.to_string())))
-            .collect()
-    };
+        if result.incomplete_results {
+            out.warn("Warning: Search timed out, results may be incomplete");
+        }
 
-    for branch in branches {
-        progress.set_message(format!("Searching in branch: {}", branch));
-        let tree_items = fetch_tree_recursive(user, repo, &branch, config, progress).await?;
+        total_count = result.total_count;
+        let page_len = result.items.len();
+        items.extend(result.items);
+
+        if page_len < per_page {
+            break;
+        }
+        page += 1;
+    }
+
+    progress.finish_and_clear();
+    items.truncate(limit);
+
+    Ok((items, total_count))
+}
 
-        for item in tree_items {
-            // Extract the filename from the path
-            if let Some(file_name) = item.path.split('/').last() {
-                // Check for exact or partial match based on the flag
-                if (exact_match && file_name == filename) || (!exact_match && file_name.contains(filename)) {
-                    println!("Found: {} in branch: {}", item.path, branch);
+fn print_code_matches(
+    out: &Output,
+    items: &[CodeSearchItem],
+    total_count: u64,
+    format: OutputFormat,
+    links: Option<LinkMode>,
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => {
+            writeln!(out, "{}", serde_json::to_string_pretty(items)?);
+        }
+        OutputFormat::Paths => {
+            for item in items {
+                writeln!(out, "{}", format_link(&item.path, &item.html_url, links));
+            }
+        }
+        OutputFormat::Text => {
+            for item in items {
+                writeln!(out, "Found: {}", format_link(&item.path.bold().to_string(), &item.html_url, links));
+                for text_match in &item.text_matches {
+                    for line in highlight_fragment(&text_match.fragment, &text_match.matches).lines() {
+                        writeln!(out, "    │ {}", line);
+                    }
+                    writeln!(out, "    │");
                 }
             }
+
+            writeln!(
+                out,
+                "Search completed: {} of {} match(es)",
+                items.len(),
+                total_count
+            );
         }
     }
 
-    progress.finish_with_message("Search completed");
     Ok(())
 }
 
@@ -772,36 +5181,87 @@ struct Cli {
     #[arg(long = "pat", global = true)]
     pat: Option<String>,
 
+    /// Run the command for every repository in a list or TOML manifest (`-` for stdin)
+    #[arg(long = "repos-file", global = true)]
+    repos_file: Option<String>,
+
+    /// Number of repositories to process concurrently with --repos-file
+    #[arg(short = 'j', long = "jobs", global = true, default_value_t = 4)]
+    jobs: usize,
+
     #[command(subcommand)]
     command: Commands,
 }
 
-#[derive(Subcommand)]
+#[derive(Subcommand, Clone)]
 enum Commands {
     /// View repository structure
     View {
         /// Repository URL or owner/repo format
-        #[arg(short = 'r', long = "repo", required = true)]
-        repo: String,
+        #[arg(short = 'r', long = "repo", required_unless_present_any = ["repos_file", "from"])]
+        repo: Option<String>,
 
         /// Branch name (default: repository's default branch)
         #[arg(short = 'b', long = "branch")]
         branch: Option<String>,
 
+        /// Save the fetched tree to a snapshot file
+        #[arg(long = "save", conflicts_with = "from")]
+        save: Option<String>,
+
+        /// Render a snapshot saved with --save instead of fetching the tree
+        #[arg(long = "from", conflicts_with_all = ["repo", "branch"])]
+        from: Option<String>,
+
         /// Specific folder to view
         #[arg(short = 'f', long = "folder")]
         folder: Option<String>,
 
-        /// Enable colored output with icons
-        #[arg(short = 'c', long = "color")]
-        color: bool,
+        /// When to color names using LS_COLORS (`-c` alone means always)
+        #[arg(
+            short = 'c',
+            long = "color",
+            value_enum,
+            default_value = "auto",
+            num_args = 0..=1,
+            default_missing_value = "always"
+        )]
+        color: ColorWhen,
+
+        /// Icon set (default: emoji when colored, none otherwise)
+        #[arg(long = "icons", value_enum)]
+        icons: Option<IconStyle>,
+
+        /// Link names to their GitHub pages
+        #[arg(long = "links", value_enum, default_value = "auto")]
+        links: LinkMode,
+
+        /// Only show entries up to this many levels deep
+        #[arg(short = 'd', long = "depth")]
+        depth: Option<usize>,
+
+        /// Annotate entries with their most recent commit (top level, or down to --depth)
+        #[arg(long = "last-commit")]
+        last_commit: bool,
+
+        /// Mark entries added or modified since this ref or date (YYYY-MM-DD or ISO 8601)
+        #[arg(long = "changed-since", value_name = "REF|DATE")]
+        changed_since: Option<String>,
+
+        /// With --changed-since, only show changed entries and their folders
+        #[arg(long = "only-changed", requires = "changed_since")]
+        only_changed: bool,
+
+        /// Output format
+        #[arg(long = "format", value_enum, default_value = "text")]
+        format: TreeFormat,
     },
 
     /// Create empty directory structure
     Touch {
         /// Repository URL or owner/repo format
-        #[arg(short = 'r', long = "repo", required = true)]
-        repo: String,
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
 
         /// Output directory path
         #[arg(short = 'o', long = "output", required = true)]
@@ -810,13 +5270,53 @@ enum Commands {
         /// Branch name (default: repository's default branch)
         #[arg(short = 'b', long = "branch")]
         branch: Option<String>,
+
+        /// Record where each placeholder comes from so `hydrate` can fill it in later
+        #[arg(long = "lazy")]
+        lazy: bool,
+
+        /// Create sparse files with each blob's real size instead of empty files
+        #[arg(long = "sized")]
+        sized: bool,
+
+        /// Write the blob SHA, size and path of every file to this manifest
+        #[arg(long = "manifest")]
+        manifest: Option<String>,
+
+        /// Reconcile an existing output directory instead of recreating every file
+        #[arg(long = "update")]
+        update: bool,
+
+        /// With --update, also reset or prune files that have real content
+        #[arg(long = "force", requires = "update")]
+        force: bool,
+
+        /// With --update, remove files and directories that no longer exist remotely
+        #[arg(long = "prune", requires = "update")]
+        prune: bool,
+    },
+
+    /// Download the real content of placeholders created with `touch --lazy`
+    Hydrate {
+        /// Placeholder files or directories, or globs relative to the placeholder root.
+        /// At least one is required; give the root directory itself to hydrate everything
+        #[arg(required = true)]
+        paths: Vec<String>,
+
+        /// Placeholder root (default: nearest parent directory with a placeholder index)
+        #[arg(short = 'd', long = "dir")]
+        dir: Option<String>,
+
+        /// Overwrite files that were modified locally
+        #[arg(long = "force")]
+        force: bool,
     },
 
     /// Pull specific file or folder
     Pull {
         /// Repository URL or owner/repo format
-        #[arg(short = 'r', long = "repo", required = true)]
-        repo: String,
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
 
         /// File or folder path to pull
         #[arg(short = 'f', long = "path", required = true)]
@@ -834,137 +5334,958 @@ enum Commands {
     /// Download repository as zip
     Download {
         /// Repository URL or owner/repo format
-        #[arg(short = 'r', long = "repo", required = true)]
-        repo: String,
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Branch name (default: repository's default branch)
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Output directory
+        #[arg(short = 'o', long = "output")]
+        output: Option<String>,
+    },
+
+    /// List branches with their head commits
+    Branches {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Only show names matching this glob (e.g. `release/*`)
+        pattern: Option<String>,
+
+        /// Sort order
+        #[arg(short = 's', long = "sort", value_enum, default_value = "name")]
+        sort: RefSort,
+
+        /// Output format
+        #[arg(long = "format", value_enum, default_value = "text")]
+        format: OutputFormat,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+    },
+
+    /// List tags with their commits
+    Tags {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Only show names matching this glob (e.g. `release/*`)
+        pattern: Option<String>,
+
+        /// Sort order
+        #[arg(short = 's', long = "sort", value_enum, default_value = "name")]
+        sort: RefSort,
+
+        /// Output format
+        #[arg(long = "format", value_enum, default_value = "text")]
+        format: OutputFormat,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+    },
+
+    /// List all repositories of a user or organization
+    Repos {
+        /// User or organization name
+        owner: String,
+
+        /// Only show repositories whose name matches this glob
+        #[arg(short = 'p', long = "pattern")]
+        pattern: Option<String>,
+
+        /// Only show repositories with this primary language
+        #[arg(short = 'l', long = "language")]
+        language: Option<String>,
+
+        /// Hide forks
+        #[arg(long = "no-forks")]
+        no_forks: bool,
+
+        /// Hide archived repositories
+        #[arg(long = "no-archived")]
+        no_archived: bool,
+
+        /// Sort order
+        #[arg(short = 's', long = "sort", value_enum, default_value = "name")]
+        sort: RepoSort,
+
+        /// Output format (`paths` prints owner/repo, one per line)
+        #[arg(long = "format", value_enum, default_value = "text")]
+        format: OutputFormat,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+    },
+
+    /// Create a git repository from the latest commit without needing git
+    Clone {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Branch, tag or commit to clone (default: repository's default branch)
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Output directory (default: repository name)
+        #[arg(short = 'o', long = "output")]
+        output: Option<String>,
+
+        /// Only fetch the latest commit (full history is not supported)
+        #[arg(long = "shallow")]
+        shallow: bool,
+    },
+
+    /// Compare a local directory against the remote tree
+    Status {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Local directory to check
+        #[arg(short = 'd', long = "dir", required = true)]
+        dir: String,
+
+        /// Remote folder the local directory corresponds to
+        #[arg(short = 'f', long = "folder")]
+        folder: Option<String>,
+
+        /// Branch name (default: repository's default branch)
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Don't fail because of local files that don't exist remotely
+        #[arg(long = "ignore-extra")]
+        ignore_extra: bool,
+
+        /// Also list identical files
+        #[arg(long = "show-identical")]
+        show_identical: bool,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+    },
+
+    /// Explore a repository in an interactive terminal browser
+    Browse {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Branch name (default: repository's default branch)
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Directory that marked files are pulled into
+        #[arg(short = 'o', long = "output")]
+        output: Option<String>,
+    },
+
+    /// Show file counts and sizes by extension and top-level directory
+    Stats {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Branch name (default: repository's default branch)
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Specific folder to analyze
+        #[arg(short = 'f', long = "folder")]
+        folder: Option<String>,
+
+        /// Only show the largest N rows of each table
+        #[arg(short = 'n', long = "top")]
+        top: Option<usize>,
+
+        /// Also show the languages API breakdown for comparison
+        #[arg(short = 'l', long = "languages")]
+        languages: bool,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+    },
+
+    /// Show repository metadata, languages and README
+    Info {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Don't render the README
+        #[arg(long = "no-readme")]
+        no_readme: bool,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+    },
+
+    /// Show commits touching a file or folder
+    Log {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Only show commits touching this file or folder
+        #[arg(short = 'f', long = "path")]
+        path: Option<String>,
+
+        /// Branch name (default: repository's default branch)
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Only show commits after this date (YYYY-MM-DD or ISO 8601)
+        #[arg(long = "since", value_parser = parse_timestamp)]
+        since: Option<String>,
+
+        /// Only show commits before this date (YYYY-MM-DD or ISO 8601)
+        #[arg(long = "until", value_parser = parse_timestamp)]
+        until: Option<String>,
+
+        /// Maximum number of commits to show
+        #[arg(short = 'n', long = "limit", default_value_t = 100)]
+        limit: usize,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+    },
+
+    /// Compare two refs as a tree diff
+    Diff {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Refs to compare, as base..head
+        range: String,
+
+        /// Show added and removed line counts
+        #[arg(long = "stat")]
+        stat: bool,
+
+        /// Enable colored output
+        #[arg(short = 'c', long = "color")]
+        color: bool,
+
+        /// Output format
+        #[arg(long = "format", value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+
+    /// Print a single file to stdout
+    Cat {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// File path, optionally suffixed with @ref (e.g. src/main.rs@v1.0)
+        path: String,
+
+        /// Branch, tag or commit (default: repository's default branch).
+        /// When given, the path is used as is, even if it contains an @
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Only print lines in this range, e.g. 10:20, 10: or :20
+        #[arg(short = 'l', long = "lines", value_parser = parse_line_range)]
+        lines: Option<LineRange>,
+
+        /// Syntax highlight the output when stdout is a terminal
+        #[arg(long = "highlight")]
+        highlight: bool,
+    },
+
+    /// Find file in repository
+    Find {
+        /// Repository URL or owner/repo format
+        #[arg(short = 'r', long = "repo", required_unless_present = "repos_file")]
+        repo: Option<String>,
+
+        /// Filename to search for
+        #[arg(
+            short = 'f',
+            long = "filename",
+            required_unless_present_any = ["content", "ext", "min_size", "max_size", "entry_type"]
+        )]
+        filename: Option<String>,
+
+        /// Search file contents via the code search API instead of names
+        #[arg(long = "content", conflicts_with = "filename")]
+        content: Option<String>,
+
+        /// Restrict content search to a path (`path:` qualifier)
+        #[arg(long = "path", requires = "content", conflicts_with = "filename")]
+        path: Option<String>,
+
+        /// Only match files with this extension (also an `extension:` qualifier for --content)
+        #[arg(long = "ext")]
+        ext: Option<String>,
+
+        /// Only match files at least this large (e.g. 10MB)
+        #[arg(long = "min-size", value_parser = parse_size, conflicts_with = "content")]
+        min_size: Option<u64>,
+
+        /// Only match files at most this large (e.g. 512K)
+        #[arg(long = "max-size", value_parser = parse_size, conflicts_with = "content")]
+        max_size: Option<u64>,
+
+        /// Only match entries of this type
+        #[arg(long = "type", value_enum, conflicts_with = "content")]
+        entry_type: Option<EntryType>,
+
+        /// Branch name (default: repository's default branch)
+        #[arg(short = 'b', long = "branch")]
+        branch: Option<String>,
+
+        /// Match the whole name exactly
+        #[arg(long = "exact", group = "mode")]
+        exact: bool,
+
+        /// Treat the filename as a regular expression
+        #[arg(long = "regex", group = "mode")]
+        regex: bool,
+
+        /// Treat the filename as a glob (e.g. `**/test_*.py`)
+        #[arg(long = "glob", group = "mode")]
+        glob: bool,
+
+        /// Fuzzy match and rank results by score
+        #[arg(long = "fuzzy", group = "mode")]
+        fuzzy: bool,
+
+        /// Case-insensitive matching
+        #[arg(short = 'i', long = "ignore-case")]
+        ignore_case: bool,
+
+        /// Match against the full path instead of the file name
+        #[arg(long = "full-path")]
+        full_path: bool,
+
+        /// Maximum number of results to print
+        #[arg(short = 'n', long = "limit")]
+        limit: Option<usize>,
+
+        /// Output format
+        #[arg(long = "format", value_enum, default_value = "text")]
+        format: OutputFormat,
+
+        /// Link each result to its GitHub page (print the URL, hyperlink, or auto)
+        #[arg(long = "links", value_enum)]
+        links: Option<LinkMode>,
+    },
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchManifest {
+    #[serde(rename = "repo", default)]
+    repos: Vec<BatchTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BatchTarget {
+    repo: String,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    #[serde(default)]
+    paths: Vec<String>,
+}
+
+struct BatchResult {
+    repo: String,
+    git_ref: Option<String>,
+    path: Option<String>,
+    elapsed: Duration,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResponse {
+    resources: RateLimitResources,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResources {
+    core: RateLimitBucket,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitBucket {
+    remaining: u64,
+    reset: u64,
+}
+
+/// Reads a repository list: either a TOML manifest with `[[repo]]` tables, or
+/// one locator per line (blank lines and `#` comments are skipped).
+fn parse_batch_targets(content: &str) -> Result<Vec<BatchTarget>> {
+    if let Ok(manifest) = toml::from_str::<BatchManifest>(content)
+        && !manifest.repos.is_empty()
+    {
+        return Ok(manifest.repos);
+    }
+
+    let targets: Vec<BatchTarget> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| BatchTarget {
+            repo: line.to_string(),
+            git_ref: None,
+            paths: Vec::new(),
+        })
+        .collect();
+
+    if targets.is_empty() {
+        return Err(anyhow!("No repositories found in repository list"));
+    }
+    Ok(targets)
+}
+
+/// Whether a subcommand takes the ref and the paths a manifest entry can set.
+/// Commands that can't run once per repository are rejected.
+fn batch_support(command: &Commands) -> Result<(bool, bool)> {
+    Ok(match command {
+        Commands::View { save: Some(_), .. } => {
+            return Err(anyhow!("view --save does not support --repos-file"));
+        }
+        Commands::View { from: Some(_), .. } => {
+            return Err(anyhow!("view --from does not support --repos-file"));
+        }
+        Commands::Touch { manifest: Some(_), .. } => {
+            return Err(anyhow!("touch --manifest does not support --repos-file"));
+        }
+        Commands::View { .. }
+        | Commands::Stats { .. }
+        | Commands::Status { .. }
+        | Commands::Pull { .. }
+        | Commands::Log { .. } => (true, true),
+        Commands::Touch { .. }
+        | Commands::Download { .. }
+        | Commands::Clone { .. }
+        | Commands::Cat { .. }
+        | Commands::Find { .. } => (true, false),
+        Commands::Diff { .. }
+        | Commands::Branches { .. }
+        | Commands::Tags { .. }
+        | Commands::Info { .. } => (false, false),
+        Commands::Repos { .. } => {
+            return Err(anyhow!("The repos command does not support --repos-file"));
+        }
+        Commands::Hydrate { .. } => {
+            return Err(anyhow!("The hydrate command does not support --repos-file"));
+        }
+        Commands::Browse { .. } => {
+            return Err(anyhow!("The browse command does not support --repos-file"));
+        }
+    })
+}
+
+/// Points a copy of `command` at one repository of a batch. Output and local
+/// directories get an `<owner>/<repo>` subdirectory so concurrent jobs don't share files.
+fn batch_command(command: &Commands, repo: &str, git_ref: Option<&str>, path: Option<&str>) -> Result<Commands> {
+    let (user, repo_name) = parse_repo_arg(Some(repo))?;
+    let repo_dir = |dir: Option<&str>| {
+        Path::new(dir.unwrap_or("."))
+            .join(&user)
+            .join(&repo_name)
+            .to_string_lossy()
+            .into_owned()
+    };
+    let set_ref = |branch: &mut Option<String>| {
+        if let Some(git_ref) = git_ref {
+            *branch = Some(git_ref.to_string());
+        }
+    };
+    let set_path = |target: &mut Option<String>| {
+        if let Some(path) = path {
+            *target = Some(path.to_string());
+        }
+    };
+
+    let mut command = command.clone();
+    match &mut command {
+        Commands::View { repo: r, branch, folder, .. } | Commands::Stats { repo: r, branch, folder, .. } => {
+            *r = Some(repo.to_string());
+            set_ref(branch);
+            set_path(folder);
+        }
+        Commands::Status { repo: r, branch, folder, dir, .. } => {
+            *r = Some(repo.to_string());
+            set_ref(branch);
+            set_path(folder);
+            *dir = repo_dir(Some(dir));
+        }
+        Commands::Pull { repo: r, branch, path: target, output } => {
+            *r = Some(repo.to_string());
+            set_ref(branch);
+            if let Some(path) = path {
+                *target = path.to_string();
+            }
+            *output = Some(repo_dir(output.as_deref()));
+        }
+        Commands::Log { repo: r, branch, path: target, .. } => {
+            *r = Some(repo.to_string());
+            set_ref(branch);
+            set_path(target);
+        }
+        Commands::Touch { repo: r, branch, output, .. } => {
+            *r = Some(repo.to_string());
+            set_ref(branch);
+            *output = repo_dir(Some(output));
+        }
+        Commands::Download { repo: r, branch, output } | Commands::Clone { repo: r, branch, output, .. } => {
+            *r = Some(repo.to_string());
+            set_ref(branch);
+            *output = Some(repo_dir(output.as_deref()));
+        }
+        Commands::Cat { repo: r, branch, .. } | Commands::Find { repo: r, branch, .. } => {
+            *r = Some(repo.to_string());
+            set_ref(branch);
+        }
+        Commands::Diff { repo: r, .. }
+        | Commands::Branches { repo: r, .. }
+        | Commands::Tags { repo: r, .. }
+        | Commands::Info { repo: r, .. } => {
+            *r = Some(repo.to_string());
+        }
+        Commands::Repos { .. } | Commands::Hydrate { .. } | Commands::Browse { .. } => {
+            unreachable!("rejected by batch_support")
+        }
+    }
+    Ok(command)
+}
+
+/// Waits for the core rate limit to reset if it is nearly exhausted.
+async fn wait_for_rate_limit(config: &Config, needed: u64, repo: &str) {
+    let url = format!("{}/rate_limit", GITHUB_API_URL);
+    let Ok(response) = config.client.get(&url).headers(config.get_headers()).send().await else {
+        return;
+    };
+    let Ok(limits) = response.json::<RateLimitResponse>().await else {
+        return;
+    };
+
+    let core = limits.resources.core;
+    if core.remaining >= needed {
+        return;
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let wait = core.reset.saturating_sub(now) + 1;
+    // Printed right away, as the job's own output only appears once it finishes
+    eprintln!(
+        "{}: Rate limit nearly exhausted ({} requests left), waiting {}s for reset",
+        repo, core.remaining, wait
+    );
+    sleep(Duration::from_secs(wait)).await;
+}
+
+async fn run_batch_target(
+    command: Commands,
+    repo: String,
+    git_ref: Option<String>,
+    path: Option<String>,
+    config: Arc<Config>,
+) -> BatchResult {
+    wait_for_rate_limit(&config, BATCH_MIN_RATE_LIMIT, &repo).await;
+
+    let started = std::time::Instant::now();
+    let out = Output::buffered();
+    let result = run_command(command, config, &out).await;
+    let output = out.into_job_output();
+
+    let mut label = format!("==> {}", repo);
+    if let Some(git_ref) = &git_ref {
+        label.push_str(&format!(" @ {}", git_ref));
+    }
+    if let Some(path) = &path {
+        label.push_str(&format!(" : {}", path));
+    }
+    println!("{}", label.bold());
+    print!("{}", output.stdout);
+    // Warnings stay on stderr, under the same header
+    if !output.stderr.is_empty() {
+        eprintln!("{}", label.bold());
+        eprint!("{}", output.stderr);
+    }
+
+    BatchResult {
+        repo,
+        git_ref,
+        path,
+        elapsed: started.elapsed(),
+        error: result.err().map(|e| format!("{:#}", e)),
+    }
+}
+
+fn print_batch_summary(results: &[BatchResult]) {
+    let target = |r: &BatchResult| {
+        let mut target = r.repo.clone();
+        if let Some(git_ref) = &r.git_ref {
+            target.push_str(&format!("@{}", git_ref));
+        }
+        if let Some(path) = &r.path {
+            target.push_str(&format!(":{}", path));
+        }
+        target
+    };
+    let width = results.iter().map(|r| target(r).len()).max().unwrap_or(0).max(10);
+
+    println!("\n{:<width$}  {:<6}  {:>8}  Error", "Repository", "Status", "Time", width = width);
+    for r in results {
+        let (status, error) = match &r.error {
+            None => ("ok".green(), ""),
+            Some(error) => ("failed".red(), error.as_str()),
+        };
+        println!(
+            "{:<width$}  {:<6}  {:>7.1}s  {}",
+            target(r),
+            status,
+            r.elapsed.as_secs_f64(),
+            error,
+            width = width
+        );
+    }
+}
 
-        /// Branch name (default: repository's default branch)
-        #[arg(short = 'b', long = "branch")]
-        branch: Option<String>,
+/// Runs the command once per repository (and manifest path) in this process.
+/// Every job's output is collected and printed in one piece, and failures
+/// don't stop the rest of the batch.
+async fn run_batch(command: &Commands, repos_file: &str, jobs: usize, config: &Arc<Config>) -> Result<()> {
+    let (takes_ref, takes_path) = batch_support(command)?;
+
+    let content = if repos_file == "-" {
+        let mut content = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut content)
+            .context("Failed to read repositories from stdin")?;
+        content
+    } else {
+        fs::read_to_string(repos_file)
+            .with_context(|| format!("Failed to read repository list: {}", repos_file))?
+    };
+    let targets = parse_batch_targets(&content)?;
 
-        /// Output directory
-        #[arg(short = 'o', long = "output")]
-        output: Option<String>,
-    },
+    let mut jobs_list = Vec::new();
+    for target in targets {
+        let git_ref = target.git_ref.filter(|_| takes_ref);
+        let paths: Vec<Option<String>> = if target.paths.is_empty() || !takes_path {
+            vec![None]
+        } else {
+            target.paths.into_iter().map(Some).collect()
+        };
 
-    /// Find file in repository
-    Find {
-        /// Repository URL or owner/repo format
-        #[arg(short = 'r', long = "repo", required = true)]
-        repo: String,
+        for path in paths {
+            let job = batch_command(command, &target.repo, git_ref.as_deref(), path.as_deref())?;
+            jobs_list.push((job, target.repo.clone(), git_ref.clone(), path));
+        }
+    }
 
-        /// Filename to search for
-        #[arg(short = 'f', long = "filename", required = true)]
-        filename: String,
+    let total = jobs_list.len();
+    let runs = jobs_list
+        .into_iter()
+        .map(|(job, repo, git_ref, path)| run_batch_target(job, repo, git_ref, path, config.clone()));
+    let mut results: Vec<BatchResult> =
+        futures::StreamExt::buffer_unordered(futures::stream::iter(runs), jobs.max(1))
+            .collect()
+            .await;
+    results.sort_by(|a, b| a.repo.cmp(&b.repo).then_with(|| a.path.cmp(&b.path)));
 
-        /// Branch name (default: repository's default branch)
-        #[arg(short = 'b', long = "branch")]
-        branch: Option<String>,
+    print_batch_summary(&results);
 
-        #[arg(long = "exact")]
-        exact: bool,
-    },
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    if failed > 0 {
+        return Err(anyhow!("{} of {} job(s) failed", failed, total));
+    }
+    Ok(())
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
     let config = if let Some(token) = cli.pat.or_else(|| env::var("GH_TOKEN").ok()) {
-        println!("Using provided PAT token to fetch data.");
+        eprintln!("Using provided PAT token to fetch data.");
         Arc::new(Config::with_token(token))
     } else {
         Arc::new(Config::new())
     };
 
-    match cli.command {
+    // The override is process-wide, so it's set once here rather than inside each batch job
+    if let Commands::View { color: ColorWhen::Always, .. } = &cli.command {
+        colored::control::set_override(true);
+    }
+
+    if let Some(repos_file) = &cli.repos_file {
+        return run_batch(&cli.command, repos_file, cli.jobs, &config).await;
+    }
+
+    match run_command(cli.command, config, &Output::default()).await {
+        // 0 = success, 1 = error, 2 = status found differences
+        Err(e) if e.is::<OutOfDate>() => std::process::exit(2),
+        result => result,
+    }
+}
+
+/// Runs one subcommand. Batch mode calls this once per repository.
+async fn run_command(command: Commands, config: Arc<Config>, out: &Output) -> Result<()> {
+    match command {
         Commands::View {
             repo,
             branch,
+            save,
+            from,
             folder,
-            color, // Add `color` here
+            color,
+            icons,
+            links,
+            depth,
+            last_commit,
+            changed_since,
+            only_changed,
+            format,
         } => {
-            let (user, repo_name) = parse_github_url(&repo)
-                .ok_or_else(|| anyhow!("Invalid GitHub URL"))?;
-
-            let progress = create_progress_bar("Fetching repository information...");
-
-            let branch = if let Some(b) = branch {
-                b
+            let color = color.enabled();
+            let links = links.resolve();
+            // Keep the emoji icons that used to come with colored output
+            let icons = icons.unwrap_or(if color { IconStyle::Emoji } else { IconStyle::None });
+
+            let progress = out.progress_bar("Fetching repository information...");
+
+            let (user, repo_name, branch, tree_items) = if let Some(from) = from {
+                progress.set_message("Reading snapshot...");
+                let snapshot = read_tree_snapshot(Path::new(&from))?;
+                let (user, repo_name) = parse_repo_arg(Some(&snapshot.repo))?;
+                // Pin follow-up requests like --last-commit to the snapshot's commit
+                (user, repo_name, snapshot.commit, snapshot.tree)
             } else {
-                fetch_repo_info(&user, &repo_name, &config).await?.default_branch
+                let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+                let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+
+                let tree_items = if let Some(save) = save {
+                    // Fetch by commit so the snapshot's tree and commit agree
+                    let commit = fetch_commit_sha(&user, &repo_name, &branch, &config).await?;
+                    let fetched_at = std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .map(|d| d.as_secs() as i64)
+                        .unwrap_or(0);
+
+                    progress.set_message("Fetching tree data...");
+                    let tree_items = fetch_tree_recursive(&user, &repo_name, &commit, &config, out).await?;
+
+                    let snapshot = TreeSnapshot {
+                        repo: format!("{}/{}", user, repo_name),
+                        git_ref: branch.clone(),
+                        commit,
+                        fetched_at: format_unix_time(fetched_at),
+                        tree: tree_items,
+                    };
+                    write_tree_snapshot(Path::new(&save), &snapshot)?;
+                    snapshot.tree
+                } else {
+                    progress.set_message("Fetching tree data...");
+                    fetch_tree_recursive(&user, &repo_name, &branch, &config, out).await?
+                };
+
+                (user, repo_name, branch, tree_items)
             };
 
-            progress.set_message("Fetching tree data...");
-            let tree_items = fetch_tree_recursive(&user, &repo_name, &branch, &config, &progress).await?;
-
-            let filtered_tree_items = if let Some(folder_path) = folder {
-                tree_items
+            let folder_prefix = folder_prefix(folder.as_deref());
+            let filtered_tree_items: Vec<TreeItem> = tree_items
+                .into_iter()
+                .filter(|item| in_folder(folder_prefix, &item.path))
+                .collect();
+
+            // --depth and --last-commit count levels from the viewed folder
+            let base_depth = folder_prefix.map_or(0, |prefix| prefix.split('/').count());
+            let path_depth = |item: &TreeItem| item.path.split('/').count() - base_depth;
+            let filtered_tree_items: Vec<TreeItem> = filtered_tree_items
+                .into_iter()
+                .filter(|item| depth.is_none_or(|depth| path_depth(item) <= depth))
+                .collect();
+
+            let mut changes = match &changed_since {
+                Some(since) => {
+                    fetch_changes_since(&user, &repo_name, &branch, since, &config, &progress, out).await?
+                }
+                None => HashMap::new(),
+            };
+            changes.retain(|path, _| in_folder(folder_prefix, path));
+
+            // Keep changed entries and the folders leading to them
+            let filtered_tree_items: Vec<TreeItem> = if only_changed {
+                let keep: HashSet<&str> = changes
+                    .keys()
+                    .flat_map(|path| {
+                        path.match_indices('/')
+                            .map(|(i, _)| &path[..i])
+                            .chain(std::iter::once(path.as_str()))
+                    })
+                    .collect();
+                filtered_tree_items
                     .into_iter()
-                    .filter(|item| item.path.starts_with(&folder_path))
+                    .filter(|item| keep.contains(item.path.as_str()))
                     .collect()
             } else {
-                tree_items
+                filtered_tree_items
+            };
+
+            let last_commits = if last_commit {
+                let annotate_depth = depth.unwrap_or(1);
+                let paths: Vec<String> = filtered_tree_items
+                    .iter()
+                    .filter(|item| path_depth(item) <= annotate_depth)
+                    .map(|item| item.path.clone())
+                    .collect();
+                fetch_last_commits(&user, &repo_name, &branch, &paths, &config, &progress).await?
+            } else {
+                HashMap::new()
             };
 
             progress.set_message("Building tree view...");
-            print_tree_colored(filtered_tree_items, &progress, color)?; // Use `color` here
+            let structure = build_tree_node(filtered_tree_items, &progress, &last_commits, &changes);
+            let title = format!("{}/{}", user, repo_name);
+
+            match format {
+                TreeFormat::Text => {
+                    let links = links.map(|mode| (mode, user.as_str(), repo_name.as_str(), branch.as_str()));
+                    let changes = changed_since.is_some().then_some(&changes);
+                    print_tree_colored(out, &structure, color, icons, links, changes)?
+                }
+                TreeFormat::Html => print_tree_html(out, &structure, &user, &repo_name, &branch),
+                TreeFormat::Markdown => print_tree_markdown(out, &structure, &title),
+                TreeFormat::Dot => print_tree_dot(out, &structure, &title),
+                TreeFormat::Mermaid => print_tree_mermaid(out, &structure, &title),
+            }
         }
 
-        Commands::Touch { repo, output, branch } => {
+        Commands::Touch {
+            repo,
+            output,
+            branch,
+            lazy,
+            sized,
+            manifest,
+            update,
+            force,
+            prune,
+        } => {
             let base_path = PathBuf::from(output);
-            let (user, repo_name) = parse_github_url(&repo)
-                .ok_or_else(|| anyhow!("Invalid GitHub URL"))?;
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
 
-            let progress = create_progress_bar("Fetching repository information...");
+            let progress = out.progress_bar("Fetching repository information...");
 
-            let branch = if let Some(b) = branch {
-                b
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+
+            progress.set_message("Fetching tree data...");
+            let tree_items = fetch_tree_recursive(&user, &repo_name, &branch, &config, out).await?;
+
+            let has_index = base_path.join(PLACEHOLDER_INDEX).is_file();
+            let placeholders = if update {
+                let previous = if has_index { Some(read_placeholder_index(&base_path)?) } else { None };
+
+                progress.set_message("Updating directory structure...");
+                let summary = reconcile_placeholder_structure(
+                    &tree_items,
+                    &base_path,
+                    previous.as_ref(),
+                    sized,
+                    force,
+                    prune,
+                    &progress,
+                )
+                .await?;
+                print_reconcile_summary(out, &summary);
+                Some(summary.placeholders)
             } else {
-                fetch_repo_info(&user, &repo_name, &config).await?.default_branch
+                progress.set_message("Creating directory structure...");
+                create_placeholder_structure(&tree_items, &base_path, sized, &progress).await?;
+                None
             };
 
-            progress.set_message("Fetching tree data...");
-            let tree_items = fetch_tree_recursive(&user, &repo_name, &branch, &config, &progress).await?;
+            // Sized placeholders can only be told apart from real files through the index.
+            // An existing index is kept in sync with the updated tree.
+            if lazy || sized || has_index {
+                let index = PlaceholderIndex::new(&user, &repo_name, &branch, &tree_items, &base_path, |path| {
+                    placeholders.as_ref().is_none_or(|placeholders| placeholders.contains(path))
+                });
+                write_placeholder_index(&base_path, &index)?;
+            }
+
+            if let Some(manifest) = manifest {
+                write_blob_manifest(Path::new(&manifest), &tree_items)?;
+            }
+        }
 
-            progress.set_message("Creating directory structure...");
-            create_placeholder_structure(tree_items, &base_path, &progress).await?;
+        Commands::Hydrate { paths, dir, force } => {
+            let root = match dir {
+                Some(dir) => PathBuf::from(dir),
+                None => {
+                    let cwd = env::current_dir().context("Failed to get current directory")?;
+                    cwd.ancestors()
+                        .find(|d| d.join(PLACEHOLDER_INDEX).is_file())
+                        .map(Path::to_path_buf)
+                        .ok_or_else(|| anyhow!("No placeholder index found, create one with `touch --lazy`"))?
+                }
+            };
+            let root = root
+                .canonicalize()
+                .with_context(|| format!("Failed to resolve path: {}", root.display()))?;
+
+            let mut index = read_placeholder_index(&root)?;
+            let selected = select_placeholders(&index, &root, &paths)?;
+            let total = selected.len();
+
+            let progress = out.progress_bar("Hydrating placeholders...");
+            let hydrated = hydrate_placeholders(&index, &root, &selected, force, &config, &progress, out).await?;
+
+            for path in &hydrated {
+                if let Some(entry) = index.files.get_mut(path) {
+                    entry.placeholder = None;
+                }
+            }
+            write_placeholder_index(&root, &index)?;
+            progress.finish_with_message(format!("Hydrated {} of {} selected files", hydrated.len(), total));
         }
 
         Commands::Pull { repo, path, branch, output } => {
-            let (user, repo_name) = parse_github_url(&repo)
-                .ok_or_else(|| anyhow!("Invalid GitHub URL"))?;
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
 
-            let progress = Arc::new(create_progress_bar("Fetching repository information..."));
+            let progress = out.progress_bar("Fetching repository information...");
 
-            let branch = if let Some(b) = branch {
-                b
-            } else {
-                fetch_repo_info(&user, &repo_name, &config).await?.default_branch
-            };
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+            progress.finish_and_clear();
 
-            progress.set_message("Downloading file/folder...");
             pull_file_or_folder(
-                &user,
-                &repo_name,
+                (&user, &repo_name),
                 &branch,
                 &path,
                 output.as_ref().map(PathBuf::from).as_deref(),
                 config,
-                progress,
+                out,
+                MAX_RETRIES,
             )
             .await?;
         }
 
         Commands::Download { repo, branch, output } => {
-            let (user, repo_name) = parse_github_url(&repo)
-                .ok_or_else(|| anyhow!("Invalid GitHub URL"))?;
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
 
-            let progress = create_progress_bar("Downloading repository zip...");
+            let progress = out.progress_bar("Downloading repository zip...");
 
-            let branch = if let Some(b) = branch {
-                b
-            } else {
-                fetch_repo_info(&user, &repo_name, &config).await?.default_branch
-            };
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
 
             download_repo_zip(
                 &user,
@@ -973,33 +6294,860 @@ async fn main() -> Result<()> {
                 output.as_ref().map(PathBuf::from).as_deref(),
                 &config,
                 &progress,
+                out,
+            )
+            .await?;
+        }
+
+        Commands::Branches {
+            repo,
+            pattern,
+            sort,
+            format,
+            color,
+        } => {
+            let with_dates = sort == RefSort::Date || format != OutputFormat::Paths;
+            let refs =
+                list_refs(repo.as_deref(), RefKind::Branch, pattern.as_deref(), sort, with_dates, &config, out).await?;
+            print_refs(out, &refs, format, color)?;
+        }
+
+        Commands::Tags {
+            repo,
+            pattern,
+            sort,
+            format,
+            color,
+        } => {
+            let with_dates = sort == RefSort::Date || format != OutputFormat::Paths;
+            let refs =
+                list_refs(repo.as_deref(), RefKind::Tag, pattern.as_deref(), sort, with_dates, &config, out).await?;
+            print_refs(out, &refs, format, color)?;
+        }
+
+        Commands::Repos {
+            owner,
+            pattern,
+            language,
+            no_forks,
+            no_archived,
+            sort,
+            format,
+            color,
+        } => {
+            let pattern = pattern
+                .map(|p| {
+                    globset::GlobBuilder::new(&p)
+                        .case_insensitive(true)
+                        .build()
+                        .map(|g| g.compile_matcher())
+                        .with_context(|| format!("Invalid glob: {}", p))
+                })
+                .transpose()?;
+
+            let progress = out.progress_bar("Fetching account information...");
+            let mut repos = fetch_owner_repos(&owner, &config, &progress).await?;
+            progress.finish_and_clear();
+
+            repos.retain(|r| {
+                pattern.as_ref().is_none_or(|p| p.is_match(&r.name))
+                    && language.as_deref().is_none_or(|l| {
+                        r.language.as_deref().is_some_and(|rl| rl.eq_ignore_ascii_case(l))
+                    })
+                    && !(no_forks && r.fork)
+                    && !(no_archived && r.archived)
+            });
+
+            match sort {
+                RepoSort::Name => repos.sort_by_key(|r| r.name.to_lowercase()),
+                RepoSort::Stars => repos.sort_by_key(|r| std::cmp::Reverse(r.stargazers_count)),
+                RepoSort::Size => repos.sort_by_key(|r| std::cmp::Reverse(r.size)),
+                RepoSort::Pushed => repos.sort_by(|a, b| b.pushed_at.cmp(&a.pushed_at)),
+            }
+
+            print_repos(out, &repos, format, color)?;
+        }
+
+        Commands::Clone {
+            repo,
+            branch,
+            output,
+            shallow,
+        } => {
+            if !shallow {
+                return Err(anyhow!("Only shallow clones are supported, pass --shallow"));
+            }
+
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+            let target = PathBuf::from(output.unwrap_or_else(|| repo_name.clone()));
+            if target.exists()
+                && fs::read_dir(&target)
+                    .with_context(|| format!("Failed to read directory: {}", target.display()))?
+                    .next()
+                    .is_some()
+            {
+                return Err(anyhow!("Destination already exists and is not empty: {}", target.display()));
+            }
+
+            let progress = out.progress_bar("Fetching repository information...");
+
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+
+            shallow_clone(&user, &repo_name, &branch, &target, &config, &progress, out).await?;
+            progress.finish_with_message(format!("Cloned {}/{} ({}) into {}", user, repo_name, branch, target.display()));
+        }
+
+        Commands::Status {
+            repo,
+            dir,
+            folder,
+            branch,
+            ignore_extra,
+            show_identical,
+            color,
+        } => {
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+            let local_dir = PathBuf::from(dir);
+            if !local_dir.is_dir() {
+                return Err(anyhow!("Not a directory: {}", local_dir.display()));
+            }
+
+            let progress = out.progress_bar("Fetching repository information...");
+
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+
+            progress.set_message("Fetching tree data...");
+            let tree_items = fetch_tree_recursive(&user, &repo_name, &branch, &config, out).await?;
+
+            progress.set_message("Comparing local files...");
+            let status = compare_local_tree(&tree_items, folder.as_deref(), &local_dir, &progress)?;
+            progress.finish_and_clear();
+
+            print_local_status(out, &status, show_identical, color);
+
+            if !status.is_clean(ignore_extra) {
+                return Err(OutOfDate.into());
+            }
+        }
+
+        Commands::Browse { repo, branch, output } => {
+            if !std::io::stdout().is_terminal() {
+                return Err(anyhow!("The browse command needs an interactive terminal"));
+            }
+
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+
+            let progress = out.progress_bar("Fetching repository information...");
+
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+            progress.finish_and_clear();
+
+            browse_repo(user, repo_name, branch, output.map(PathBuf::from), &config).await?;
+        }
+
+        Commands::Stats {
+            repo,
+            branch,
+            folder,
+            top,
+            languages,
+            color,
+        } => {
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+
+            let progress = out.progress_bar("Fetching repository information...");
+
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+
+            progress.set_message("Fetching tree data...");
+            let mut tree_items = fetch_tree_recursive(&user, &repo_name, &branch, &config, out).await?;
+
+            if let Some(prefix) = folder_prefix(folder.as_deref()) {
+                tree_items.retain(|item| item.path != prefix && in_folder(Some(prefix), &item.path));
+                if tree_items.is_empty() {
+                    return Err(anyhow!("Folder not found: {}", prefix));
+                }
+                for item in &mut tree_items {
+                    item.path = item.path[prefix.len() + 1..].to_string();
+                }
+            }
+
+            let languages = if languages {
+                progress.set_message("Fetching languages...");
+                Some(fetch_languages(&user, &repo_name, &config).await?)
+            } else {
+                None
+            };
+            progress.finish_and_clear();
+
+            print_tree_stats(out, &collect_tree_stats(&tree_items), top, color);
+            if let Some(languages) = languages {
+                print_languages(out, &languages, color);
+            }
+        }
+
+        Commands::Info {
+            repo,
+            no_readme,
+            color,
+        } => {
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+
+            let progress = out.progress_bar("Fetching repository information...");
+            let info = fetch_repo_info(&user, &repo_name, &config).await?;
+
+            progress.set_message("Fetching languages...");
+            let languages = fetch_languages(&user, &repo_name, &config).await?;
+
+            let readme = if no_readme {
+                None
+            } else {
+                progress.set_message("Fetching README...");
+                fetch_readme(&user, &repo_name, &config).await?
+            };
+            progress.finish_and_clear();
+
+            print_repo_info(out, &info, color);
+            print_languages(out, &languages, color);
+
+            if let Some(readme) = readme {
+                writeln!(out);
+                if std::io::stdout().is_terminal() {
+                    write!(out, "{}", termimad::term_text(&readme));
+                } else {
+                    writeln!(out, "{}", readme);
+                }
+            }
+        }
+
+        Commands::Log {
+            repo,
+            path,
+            branch,
+            since,
+            until,
+            limit,
+            color,
+        } => {
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+
+            let progress = out.progress_bar("Fetching repository information...");
+
+            let branch = resolve_branch(&user, &repo_name, branch, &config).await?;
+
+            progress.set_message("Fetching commits...");
+            let commits = fetch_commit_log(
+                &user,
+                &repo_name,
+                &branch,
+                path.as_deref(),
+                (since.as_deref(), until.as_deref()),
+                limit,
+                &config,
             )
             .await?;
+            progress.finish_and_clear();
+
+            print_commit_log(out, &commits, color);
+        }
+
+        Commands::Diff {
+            repo,
+            range,
+            stat,
+            color,
+            format,
+        } => {
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+            let (base, head) = parse_ref_range(&range)
+                .ok_or_else(|| anyhow!("Invalid range, expected base..head: {}", range))?;
+
+            let progress = out.progress_bar("Comparing refs...");
+            let diff = fetch_tree_diff(&user, &repo_name, &base, &head, &config, &progress, out).await?;
+
+            match format {
+                OutputFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&diff)?),
+                OutputFormat::Paths => {
+                    for file in &diff.files {
+                        writeln!(out, "{}", file.filename);
+                    }
+                }
+                OutputFormat::Text => print_tree_diff(out, &diff, stat, color),
+            }
+        }
+
+        Commands::Cat {
+            repo,
+            path,
+            branch,
+            lines,
+            highlight,
+        } => {
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+
+            // An explicit --branch takes the whole argument as the path, for names like a@b.md
+            let (path, git_ref) = match branch.as_deref() {
+                Some(branch) => (path.as_str(), Some(branch)),
+                None => split_path_ref(&path),
+            };
+            let highlight = highlight && std::io::stdout().is_terminal();
+
+            cat_file(out, (&user, &repo_name), path, git_ref, lines, highlight, &config).await?;
         }
 
         Commands::Find {
             repo,
             filename,
+            content,
+            path,
+            ext,
+            min_size,
+            max_size,
+            entry_type,
             branch,
             exact,
+            regex,
+            glob,
+            fuzzy,
+            ignore_case,
+            full_path,
+            limit,
+            format,
+            links,
         } => {
-            let (user, repo_name) = parse_github_url(&repo)
-                .ok_or_else(|| anyhow!("Invalid GitHub URL"))?;
+            let (user, repo_name) = parse_repo_arg(repo.as_deref())?;
+
+            if let Some(query) = content {
+                if branch.is_some() {
+                    out.warn("Note: code search only covers the default branch; --branch is ignored");
+                }
 
-            let progress = create_progress_bar("Searching for file...");
+                let progress = out.progress_bar("Searching code...");
+                let q = build_code_query(&user, &repo_name, &query, path.as_deref(), ext.as_deref());
+                let (items, total_count) = search_code_in_repo(&q, limit, &config, &progress, out).await?;
+                print_code_matches(out, &items, total_count, format, links.and_then(LinkMode::resolve))?;
+                return Ok(());
+            }
+            let filename = filename.unwrap_or_default();
+
+            let mode = if exact {
+                MatchMode::Exact
+            } else if regex {
+                MatchMode::Regex
+            } else if glob {
+                MatchMode::Glob
+            } else if fuzzy {
+                MatchMode::Fuzzy
+            } else {
+                MatchMode::Substring
+            };
+            let filter = EntryFilter {
+                extension: ext.map(|e| e.trim_start_matches('.').to_string()),
+                min_size,
+                max_size,
+                entry_type,
+            };
+            let matcher = NameMatcher::new(&filename, mode, ignore_case, full_path)?.with_filter(filter);
 
-            find_file_in_repo(
+            let matches = find_file_in_repo(
                 &user,
                 &repo_name,
-                &filename,
+                &matcher,
                 branch.as_deref(),
+                limit,
                 &config,
-                &progress,
-                exact,
+                out,
             )
             .await?;
+            let links = links.and_then(LinkMode::resolve);
+            print_find_matches(out, &matches, matcher.filter.has_size_bounds(), format, links)?;
         }
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+
+    #[test]
+    fn test_ls_colors_parse() {
+        let node = |item_type: &str, mode: &str| TreeNode {
+            item_type: Some(item_type.to_string()),
+            mode: Some(mode.to_string()),
+            ..TreeNode::default()
+        };
+        let dir = node("tree", "040000");
+        let file = node("blob", "100644");
+        let exec = node("blob", "100755");
+        let link = node("blob", "120000");
+
+        let cases = [
+            ("di=01;34:*.rs=33", "src", &dir, Some("01;34")),
+            ("di=01;34:*.rs=33", "main.rs", &file, Some("33")),
+            ("di=01;34:*.rs=33", "MAIN.RS", &file, Some("33")),
+            ("*.RS=33", "main.rs", &file, Some("33")),
+            ("di=01;34:*.rs=33", "README", &file, None),
+            ("fi=37:*.rs=33", "README", &file, Some("37")),
+            // Later entries win
+            ("*.rs=33:*.rs=35", "main.rs", &file, Some("35")),
+            ("*.gz=31:*.tar.gz=32", "a.tar.gz", &file, Some("32")),
+            // Type styles take precedence over extensions
+            ("ex=01;32:*.sh=33", "run.sh", &exec, Some("01;32")),
+            ("ln=target:*.md=33", "link.md", &link, None),
+            ("ln=01;36", "link", &link, Some("01;36")),
+            ("fi=0", "README", &file, None),
+            ("fi=00", "README", &file, None),
+            ("fi=", "README", &file, None),
+            // Malformed entries are skipped
+            ("di:*.rs=33", "src", &dir, None),
+            ("=01;31:*.rs=33", "main.rs", &file, Some("33")),
+            ("*=01;31", "README", &file, None),
+            ("::*.rs=33::", "main.rs", &file, Some("33")),
+            ("", "main.rs", &file, None),
+        ];
+        for (spec, name, node, expected) in cases {
+            assert_eq!(LsColors::parse(spec).style(name, node), expected, "{:?} {:?}", spec, name);
+        }
+    }
+
+    #[test]
+    fn test_parse_ref_range() {
+        let cases = [
+            ("v1.0..main", Some(("v1.0", "main"))),
+            ("v1.0...main", Some(("v1.0", "main"))),
+            ("main..v1.0", Some(("main", "v1.0"))),
+            ("release/1.x..feature/a", Some(("release/1.x", "feature/a"))),
+            ("abc123..def456", Some(("abc123", "def456"))),
+            ("main", None),
+            ("..main", None),
+            ("main..", None),
+            ("...", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            let expected = expected.map(|(base, head)| (base.to_string(), head.to_string()));
+            assert_eq!(parse_ref_range(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let cases = [
+            ("2024-01-05", Some("2024-01-05T00:00:00Z")),
+            (" 2024-01-05 ", Some("2024-01-05T00:00:00Z")),
+            ("2024-01-05T10:00:00Z", Some("2024-01-05T10:00:00Z")),
+            ("2024-01-05 10:00", None),
+            ("2024-1-5", None),
+            ("", None),
+            ("2024-01-0\u{e9}T", None),
+            ("\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}\u{e9}", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_timestamp(input).ok().as_deref(), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_unix_time() {
+        let cases = [
+            ("1970-01-01T00:00:00Z", Some(0)),
+            ("1969-12-31T23:59:59Z", Some(-1)),
+            ("2000-02-29T12:00:00Z", Some(951_825_600)),
+            ("2024-03-01T04:30:00Z", Some(1_709_267_400)),
+            ("2024-03-01T04:30:00+00:00", Some(1_709_267_400)),
+            ("2038-01-19T03:14:08Z", Some(1 << 31)),
+            ("2024-03-01T04:30:00", None),
+            ("2024-03-01T10:00:00+05:30", None),
+            ("2024-03-01Z", None),
+            ("2024-03T04:30:00Z", None),
+            ("2024-03-01T04:30Z", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(unix_time(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_format_unix_time() {
+        let cases = [
+            (0, "1970-01-01T00:00:00Z"),
+            (-1, "1969-12-31T23:59:59Z"),
+            (-86_400, "1969-12-31T00:00:00Z"),
+            (951_825_600, "2000-02-29T12:00:00Z"),
+            (951_868_800, "2000-03-01T00:00:00Z"),
+            (1_709_267_400, "2024-03-01T04:30:00Z"),
+            (4_107_542_399, "2100-02-28T23:59:59Z"),
+            (4_107_542_400, "2100-03-01T00:00:00Z"),
+        ];
+        for (secs, expected) in cases {
+            assert_eq!(format_unix_time(secs), expected, "{}", secs);
+            assert_eq!(unix_time(expected), Some(secs), "{}", expected);
+        }
+    }
+
+    // Built with git from the same files, author at +05:30 and committer at -08:00
+    const CLONE_COMMIT: &str = "9d437d4c54c89312b79780aa12dc3ee35bd2688b";
+    const CLONE_ROOT_TREE: &str = "58c8158018f7b43d588b4f3a9d43c0c67e948a0d";
+
+    fn tree_item(path: &str, mode: &str, item_type: &str, sha: &str) -> TreeItem {
+        TreeItem {
+            path: path.to_string(),
+            mode: mode.to_string(),
+            item_type: item_type.to_string(),
+            size: None,
+            sha: sha.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_shallow_clone_objects() {
+        let dir = std::env::temp_dir().join(format!("ghtree-clone-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let git_dir = dir.join(".git");
+        fs::create_dir_all(git_dir.join("refs/heads")).unwrap();
+
+        let files: [(&str, &str, &[u8], &str); 5] = [
+            ("README.md", "100644", b"hello\n", "ce013625030ba8dba906f756967f9e9ca394464a"),
+            ("link", "120000", b"README.md", "42061c01a1c70097d1e4579f29a5adf40abdec95"),
+            ("run.sh", "100755", b"#!/bin/sh\n", "1a2485251c33a70432394c93fb89330ef214bfc9"),
+            ("src.rs", "100644", b"x\n", "587be6b4c3f93f93c489c0111bba5596147a26cb"),
+            ("src/main.rs", "100644", b"fn main() {}\n", "f328e4d9d04c31d0d70d16d21a07d1613be9d577"),
+        ];
+        let mut items = vec![tree_item("src", "040000", "tree", "5d90422423db5ef6b431e8b9e60e0baf04b8742a")];
+        for (path, mode, content, sha) in files {
+            assert_eq!(write_loose_object(&git_dir, "blob", content).unwrap(), sha, "{}", path);
+            write_work_file(&dir.join(path), mode, content).unwrap();
+            items.push(tree_item(path, mode, "blob", sha));
+        }
+
+        // Fails unless every rebuilt tree hashes to its recorded id
+        write_tree_objects(&git_dir, CLONE_ROOT_TREE, &items).unwrap();
+
+        // The API reports both dates in UTC and drops the timezones
+        let commit: GitCommitObject = serde_json::from_value(serde_json::json!({
+            "sha": CLONE_COMMIT,
+            "tree": { "sha": CLONE_ROOT_TREE },
+            "parents": [],
+            "author": { "name": "Jane Doe", "email": "jane@example.com", "date": "2024-03-01T04:30:00Z" },
+            "committer": { "name": "John Roe", "email": "john@example.com", "date": "2024-03-02T16:30:00Z" },
+            "message": "Initial commit",
+            "verification": { "signature": null, "payload": null }
+        }))
+        .unwrap();
+        let raw = reconstruct_commit(&commit).expect("commit should be reproducible");
+        assert_eq!(write_loose_object(&git_dir, "commit", &raw).unwrap(), CLONE_COMMIT);
+
+        fs::write(git_dir.join("HEAD"), "ref: refs/heads/main\n").unwrap();
+        fs::write(git_dir.join("refs/heads/main"), format!("{}\n", CLONE_COMMIT)).unwrap();
+        fs::write(git_dir.join("config"), "[core]\n\trepositoryformatversion = 0\n\tbare = false\n").unwrap();
+        write_git_index(&git_dir, &dir, &items).unwrap();
+
+        // Let git itself check the objects and the index when it is installed
+        let git = |args: &[&str]| Command::new("git").arg("-C").arg(&dir).args(args).output();
+        if let Ok(fsck) = git(&["fsck", "--strict"]) {
+            assert!(fsck.status.success(), "{}", String::from_utf8_lossy(&fsck.stderr));
+            let status = git(&["status", "--porcelain"]).unwrap();
+            assert!(status.status.success(), "{}", String::from_utf8_lossy(&status.stderr));
+            assert_eq!(String::from_utf8_lossy(&status.stdout), "");
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_is_placeholder() {
+        let dir = std::env::temp_dir().join(format!("ghtree-placeholder-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let item = |path: &str| TreeItem {
+            path: path.to_string(),
+            mode: "100644".to_string(),
+            item_type: "blob".to_string(),
+            size: Some(4096),
+            sha: "0".repeat(40),
+        };
+        let items = [item("empty"), item("sized"), item("real")];
+
+        fs::write(dir.join("empty"), "").unwrap();
+        fs::File::create(dir.join("sized")).unwrap().set_len(4096).unwrap();
+        fs::write(dir.join("real"), "content").unwrap();
+        let index = PlaceholderIndex::new("u", "r", "main", &items, &dir, |path| path != "real");
+
+        let check = |index: Option<&PlaceholderIndex>, path: &str| {
+            let local_path = dir.join(path);
+            is_placeholder(index, path, &local_path, &fs::symlink_metadata(&local_path).unwrap())
+        };
+        let cases = [
+            (Some(&index), "empty", true),
+            (None, "empty", true),
+            (Some(&index), "sized", true),
+            (None, "sized", false),
+            (Some(&index), "real", false),
+        ];
+        for (index, path, expected) in cases {
+            assert_eq!(check(index, path), expected, "{} with index: {}", path, index.is_some());
+        }
+
+        // Touching a sized placeholder makes it count as a real file
+        let sized = fs::File::options().write(true).open(dir.join("sized")).unwrap();
+        let modified = sized.metadata().unwrap().modified().unwrap();
+        sized.set_modified(modified + std::time::Duration::from_secs(1)).unwrap();
+        assert!(!check(Some(&index), "sized"));
+
+        // So does a same-size edit that leaves the time unchanged, as on filesystems with coarse timestamps
+        fs::write(dir.join("sized"), vec![b'x'; 4096]).unwrap();
+        sized.set_modified(modified).unwrap();
+        assert!(!check(Some(&index), "sized"));
+
+        // Restoring the content and time makes it a placeholder again
+        fs::write(dir.join("sized"), vec![0; 4096]).unwrap();
+        sized.set_modified(modified).unwrap();
+        assert!(check(Some(&index), "sized"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_select_placeholders() {
+        let root = std::env::temp_dir().join(format!("ghtree-select-{}", std::process::id()));
+        fs::create_dir_all(root.join("docs")).unwrap();
+        let root = root.canonicalize().unwrap();
+        let items: Vec<TreeItem> = ["README.md", "docs/a.md", "docs/b.txt"]
+            .iter()
+            .map(|path| {
+                fs::write(root.join(path), "").unwrap();
+                TreeItem {
+                    path: path.to_string(),
+                    mode: "100644".to_string(),
+                    item_type: "blob".to_string(),
+                    size: None,
+                    sha: "0".repeat(40),
+                }
+            })
+            .collect();
+        let index = PlaceholderIndex::new("u", "r", "main", &items, &root, |_| true);
+        let abs = |path: &str| root.join(path).to_string_lossy().into_owned();
+
+        let cases: [(Vec<String>, Option<Vec<&str>>); 7] = [
+            (vec![], None),
+            (vec![abs("")], Some(vec!["README.md", "docs/a.md", "docs/b.txt"])),
+            (vec![abs("docs")], Some(vec!["docs/a.md", "docs/b.txt"])),
+            (vec![abs("README.md")], Some(vec!["README.md"])),
+            (vec!["docs/*.md".to_string()], Some(vec!["docs/a.md"])),
+            (vec!["*.md".to_string()], Some(vec!["README.md"])),
+            (vec!["*.rs".to_string()], None),
+        ];
+        for (targets, expected) in cases {
+            let selected = select_placeholders(&index, &root, &targets).ok();
+            assert_eq!(selected, expected, "{:?}", targets);
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_line_range() {
+        let cases = [
+            ("10:20", Some((10, Some(20)))),
+            ("10:", Some((10, None))),
+            (":20", Some((1, Some(20)))),
+            ("7", Some((7, Some(7)))),
+            (" 3 : 4 ", Some((3, Some(4)))),
+            ("5:5", Some((5, Some(5)))),
+            ("", Some((1, None))),
+            (":", Some((1, None))),
+            ("20:10", None),
+            ("0:5", None),
+            ("0", None),
+            ("a:b", None),
+            ("-1:2", None),
+            ("1:2:3", None),
+        ];
+        for (input, expected) in cases {
+            let parsed = parse_line_range(input).ok().map(|range| (range.start, range.end));
+            assert_eq!(parsed, expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_split_path_ref() {
+        let cases = [
+            ("src/main.rs", ("src/main.rs", None)),
+            ("src/main.rs@v1.0", ("src/main.rs", Some("v1.0"))),
+            ("Cargo.toml@abc123", ("Cargo.toml", Some("abc123"))),
+            ("node_modules/@types/node/index.d.ts", ("node_modules/@types/node/index.d.ts", None)),
+            ("node_modules/@types", ("node_modules/@types", None)),
+            ("@types/node", ("@types/node", None)),
+            ("src/main.rs@", ("src/main.rs@", None)),
+            ("@v1.0", ("@v1.0", None)),
+            ("src/main.rs@feature/x", ("src/main.rs@feature/x", None)),
+            ("", ("", None)),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(split_path_ref(input), expected, "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_github_url() {
+        let cases = [
+            ("main", "src/main.rs", false, "https://github.com/u/r/blob/main/src/main.rs"),
+            ("feature/x", "src", true, "https://github.com/u/r/tree/feature/x/src"),
+            ("main", "docs/a b#1?.md", false, "https://github.com/u/r/blob/main/docs/a%20b%231%3F.md"),
+            ("main", "100%.txt", false, "https://github.com/u/r/blob/main/100%25.txt"),
+            ("v1.0+rc", "caf\u{e9}.rs", false, "https://github.com/u/r/blob/v1.0%2Brc/caf%C3%A9.rs"),
+        ];
+        for (git_ref, path, is_dir, expected) in cases {
+            assert_eq!(github_url("u", "r", git_ref, path, is_dir), expected, "{:?}", path);
+        }
+    }
+
+    #[test]
+    fn test_in_folder() {
+        let cases = [
+            (None, "src/main.rs", true),
+            (Some("src"), "src", true),
+            (Some("src"), "src/main.rs", true),
+            (Some("src/sub"), "src/sub/a.rs", true),
+            (Some("src"), "srcs/main.rs", false),
+            (Some("src/sub"), "src/subway.rs", false),
+            (Some("src"), "README.md", false),
+        ];
+        for (prefix, path, expected) in cases {
+            assert_eq!(in_folder(prefix, path), expected, "{:?} {:?}", prefix, path);
+        }
+
+        assert_eq!(folder_prefix(Some("/src/sub/")), Some("src/sub"));
+        assert_eq!(folder_prefix(Some("/")), None);
+        assert_eq!(folder_prefix(None), None);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        let cases = [
+            ("0", Some(0)),
+            ("512", Some(512)),
+            ("512b", Some(512)),
+            ("1k", Some(1024)),
+            ("1.5K", Some(1536)),
+            ("10 MB", Some(10 << 20)),
+            ("2MiB", Some(2 << 20)),
+            ("1g", Some(1 << 30)),
+            ("1TB", Some(1 << 40)),
+            (" 3kb ", Some(3072)),
+            ("", None),
+            ("k", None),
+            (".", None),
+            ("-1", None),
+            ("1x", None),
+            ("1e3", None),
+            ("1.2.3k", None),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(parse_size(input).ok(), expected, "{:?}", input);
+        }
+    }
+
+    type BatchTarget<'a> = (&'a str, Option<&'a str>, Vec<&'a str>);
+
+    #[test]
+    fn test_parse_batch_targets() {
+        let cases: [(&str, Option<Vec<BatchTarget>>); 5] = [
+            (
+                "user/repo\n\n# comment\n  https://github.com/org/other  \n",
+                Some(vec![("user/repo", None, vec![]), ("https://github.com/org/other", None, vec![])]),
+            ),
+            (
+                "[[repo]]\nrepo = \"user/repo\"\nref = \"v1.2.0\"\npaths = [\"src\", \"Cargo.toml\"]\n\n[[repo]]\nrepo = \"org/other\"\n",
+                Some(vec![
+                    ("user/repo", Some("v1.2.0"), vec!["src", "Cargo.toml"]),
+                    ("org/other", None, vec![]),
+                ]),
+            ),
+            ("", None),
+            ("# only a comment\n\n", None),
+            // A manifest without [[repo]] tables is read as a plain list
+            ("title = \"x\"", Some(vec![("title = \"x\"", None, vec![])])),
+        ];
+
+        for (input, expected) in cases {
+            let parsed = parse_batch_targets(input).ok().map(|targets| {
+                targets
+                    .into_iter()
+                    .map(|t| (t.repo, t.git_ref, t.paths))
+                    .collect::<Vec<_>>()
+            });
+            let expected = expected.map(|targets| {
+                targets
+                    .into_iter()
+                    .map(|(repo, git_ref, paths)| {
+                        (
+                            repo.to_string(),
+                            git_ref.map(str::to_string),
+                            paths.into_iter().map(str::to_string).collect::<Vec<_>>(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            });
+            assert_eq!(parsed, expected, "{:?}", input);
+        }
+    }
+
+    fn parse_command(args: &[&str]) -> Commands {
+        Cli::try_parse_from(["ghtree"].iter().chain(args).chain(&["--repos-file", "-"]))
+            .unwrap()
+            .command
+    }
+
+    #[test]
+    fn test_batch_command() {
+        let dir = |parts: &[&str]| parts.iter().collect::<std::path::PathBuf>().to_string_lossy().into_owned();
+
+        let cases = [
+            (vec!["pull", "-f", "src"], Some("main"), None, "a/b", Some(dir(&[".", "a", "b"]))),
+            (vec!["pull", "-f", "src", "-o", "out"], None, Some("docs"), "a/b", Some(dir(&["out", "a", "b"]))),
+            (vec!["touch", "-o", "out"], None, None, "https://github.com/a/b", Some(dir(&["out", "a", "b"]))),
+            (vec!["status", "-d", "vendor"], None, None, "a/b", Some(dir(&["vendor", "a", "b"]))),
+            (vec!["clone", "--shallow"], None, None, "a/b", Some(dir(&[".", "a", "b"]))),
+            (vec!["download", "-o", "zips"], None, None, "a/b", Some(dir(&["zips", "a", "b"]))),
+            (vec!["info"], None, None, "a/b", None),
+        ];
+
+        for (args, git_ref, path, repo, expected_dir) in cases {
+            let command = batch_command(&parse_command(&args), repo, git_ref, path).unwrap();
+            let (repo_arg, branch, target, output) = match command {
+                Commands::Pull { repo, branch, path, output } => (repo, branch, Some(path), output),
+                Commands::Touch { repo, branch, output, .. } => (repo, branch, None, Some(output)),
+                Commands::Status { repo, branch, folder, dir, .. } => (repo, branch, folder, Some(dir)),
+                Commands::Clone { repo, branch, output, .. } | Commands::Download { repo, branch, output } => {
+                    (repo, branch, None, output)
+                }
+                Commands::Info { repo, .. } => (repo, None, None, None),
+                _ => unreachable!(),
+            };
+            assert_eq!(repo_arg.as_deref(), Some(repo), "{:?}", args);
+            assert_eq!(branch.as_deref(), git_ref, "{:?}", args);
+            if let Some(path) = path {
+                assert_eq!(target.as_deref(), Some(path), "{:?}", args);
+            }
+            assert_eq!(output, expected_dir, "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_batch_support() {
+        let cases = [
+            (vec!["view"], true),
+            (vec!["view", "--save", "tree.json"], false),
+            (vec!["touch", "-o", "out", "--manifest", "m.txt"], false),
+            (vec!["hydrate", "src"], false),
+            (vec!["repos", "user"], false),
+        ];
+        for (args, supported) in cases {
+            assert_eq!(batch_support(&parse_command(&args)).is_ok(), supported, "{:?}", args);
+        }
+    }
+}